@@ -1,17 +1,15 @@
+use crate::arithmetic::{self, OverflowError};
+use crate::puzzle_reader;
 use std::collections::HashMap;
 
-pub fn sort_parts(file: &str) -> usize {
-    let mut file_split = file.split("\n\n");
+pub fn sort_parts(file: &str) -> Result<u128, OverflowError> {
+    let mut file_split = puzzle_reader::blocks(file);
 
     let mut workflows_map: HashMap<String, Workflow> = HashMap::new();
 
     let worflows_block = file_split.next().unwrap();
 
-    for line in worflows_block.split("\n") {
-        if line.is_empty() {
-            continue;
-        }
-
+    for line in worflows_block {
         let workflow = Workflow::parse(line);
 
         workflows_map.insert(workflow.name.clone(), workflow);
@@ -21,8 +19,7 @@ pub fn sort_parts(file: &str) -> usize {
         .next()
         .map(|parts_block| {
             parts_block
-                .split("\n")
-                .filter(|line| !line.is_empty())
+                .into_iter()
                 .map(Part::parse)
                 .collect::<Vec<Part>>()
         })
@@ -46,23 +43,21 @@ pub fn sort_parts(file: &str) -> usize {
         }
     }
 
-    accepted_parts
+    let ratings = accepted_parts
         .iter()
-        .fold(0, |acc, part| acc + part.get_properties_sum())
+        .map(|part| part.get_properties_sum())
+        .collect::<Result<Vec<u128>, OverflowError>>()?;
+    arithmetic::sum(ratings, "sum of accepted part ratings")
 }
 
 pub fn calculate_all_rating_combinations(file: &str) -> usize {
-    let mut file_split = file.split("\n\n");
+    let mut file_split = puzzle_reader::blocks(file);
 
     let mut workflows_map: HashMap<String, Workflow> = HashMap::new();
 
     let worflows_block = file_split.next().unwrap();
 
-    for line in worflows_block.split("\n") {
-        if line.is_empty() {
-            continue;
-        }
-
+    for line in worflows_block {
         let workflow = Workflow::parse(line);
 
         workflows_map.insert(workflow.name.clone(), workflow);
@@ -71,7 +66,7 @@ pub fn calculate_all_rating_combinations(file: &str) -> usize {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct Workflow {
+pub struct Workflow {
     name: String,
     conditions: Vec<Condition>,
     default_result: WorkflowResult,
@@ -125,7 +120,7 @@ impl Workflow {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct Condition {
+pub struct Condition {
     property: String,
     comparator: Comparator,
     value_to_compare: usize,
@@ -185,7 +180,7 @@ impl Condition {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Comparator {
+pub enum Comparator {
     GreaterThan,
     LesserThan,
 }
@@ -201,7 +196,7 @@ impl Comparator {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-enum WorkflowResult {
+pub enum WorkflowResult {
     AnotherWorkflow(String),
     Accept,
     Reject,
@@ -218,7 +213,7 @@ impl WorkflowResult {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Part {
+pub struct Part {
     x: usize,
     m: usize,
     a: usize,
@@ -255,7 +250,8 @@ impl Part {
         }
     }
 
-    pub fn get_properties_sum(&self) -> usize {
-        self.x + self.m + self.a + self.s
+    pub fn get_properties_sum(&self) -> Result<u128, OverflowError> {
+        let properties = [self.x, self.m, self.a, self.s].map(|property| property as u128);
+        arithmetic::sum(properties, "part rating")
     }
 }
@@ -0,0 +1,83 @@
+use std::fmt;
+
+// Answers that can grow large are computed in 128 bits with checked operations. Should even that
+// overflow, the solvers report it instead of wrapping around to a wrong answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OverflowError {
+    pub operation: &'static str,
+}
+
+impl fmt::Display for OverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Arithmetic overflow while computing the {}", self.operation)
+    }
+}
+
+// Only the helpers below are generic. Each solver computes in one fixed type: u128 for counts,
+// sums and products, i64 for dig plan coordinates and oasis histories, and i128 for the areas the
+// dig plans enclose.
+pub trait CheckedNumber: Copy {
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_checked_number {
+    ($($number:ty),*) => {
+        $(
+            impl CheckedNumber for $number {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$number>::checked_add(self, other)
+                }
+
+                fn checked_sub(self, other: Self) -> Option<Self> {
+                    <$number>::checked_sub(self, other)
+                }
+
+                fn checked_mul(self, other: Self) -> Option<Self> {
+                    <$number>::checked_mul(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_checked_number!(i64, u128, i128);
+
+pub fn add<T: CheckedNumber>(a: T, b: T, operation: &'static str) -> Result<T, OverflowError> {
+    a.checked_add(b).ok_or(OverflowError { operation })
+}
+
+pub fn sub<T: CheckedNumber>(a: T, b: T, operation: &'static str) -> Result<T, OverflowError> {
+    a.checked_sub(b).ok_or(OverflowError { operation })
+}
+
+pub fn mul<T: CheckedNumber>(a: T, b: T, operation: &'static str) -> Result<T, OverflowError> {
+    a.checked_mul(b).ok_or(OverflowError { operation })
+}
+
+pub fn sum<T, I>(values: I, operation: &'static str) -> Result<T, OverflowError>
+where
+    T: CheckedNumber,
+    I: IntoIterator<Item = T>,
+{
+    values
+        .into_iter()
+        .try_fold(T::ZERO, |acc, value| add(acc, value, operation))
+}
+
+pub fn product<T, I>(values: I, operation: &'static str) -> Result<T, OverflowError>
+where
+    T: CheckedNumber,
+    I: IntoIterator<Item = T>,
+{
+    values
+        .into_iter()
+        .try_fold(T::ONE, |acc, value| mul(acc, value, operation))
+}
@@ -1,5 +1,8 @@
+use crate::arithmetic::{self, OverflowError};
+use crate::puzzle_reader;
+
 pub fn calculate_winning_possibilities(file: &str) -> usize {
-    let mut file_split = file.split("\n");
+    let mut file_split = puzzle_reader::lines(file);
     let maybe_race_time = file_split.next().and_then(parse_line_part_two);
     let maybe_record_distance = file_split.next().and_then(parse_line_part_two);
 
@@ -11,27 +14,21 @@ pub fn calculate_winning_possibilities(file: &str) -> usize {
     }
 }
 
-pub fn calculate_race_winning_margin(file: &str) -> usize {
+// A race that can not be won leaves no margin at all, so it zeroes the product
+pub fn calculate_race_winning_margin(file: &str) -> Result<u128, OverflowError> {
     let race_stats = decode_race_stats_part_one(file);
-    race_stats
-        .into_iter()
-        .fold(0, |acc, (race_time, record_distance)| {
-            let winning_possibilities =
-                get_all_possible_winning_charging_times(&race_time, &record_distance).len();
-            if winning_possibilities > 0 {
-                if acc == 0 {
-                    winning_possibilities
-                } else {
-                    acc * winning_possibilities
-                }
-            } else {
-                acc
-            }
-        })
+    if race_stats.is_empty() {
+        return Ok(0);
+    }
+
+    let winning_possibilities = race_stats.iter().map(|(race_time, record_distance)| {
+        get_all_possible_winning_charging_times(race_time, record_distance).len() as u128
+    });
+    arithmetic::product(winning_possibilities, "race winning margin")
 }
 
 fn decode_race_stats_part_one(file: &str) -> Vec<(usize, usize)> {
-    let mut file_split = file.split("\n");
+    let mut file_split = puzzle_reader::lines(file);
     let race_times = file_split.next().map(parse_line_part_one).unwrap_or(vec![]);
     let record_distances = file_split.next().map(parse_line_part_one).unwrap_or(vec![]);
 
@@ -56,30 +53,14 @@ fn parse_line_part_two(line: &str) -> Option<usize> {
         .and_then(|s| s.parse::<usize>().ok())
 }
 
+// Holding the button for no time or for the whole race does not move the boat at all
 fn get_all_possible_winning_charging_times(
     race_time: &usize,
     record_distance: &usize,
 ) -> Vec<usize> {
-    if *race_time < 2 {
-        vec![]
-    } else if *race_time == 2 {
-        if *record_distance < 1 {
-            vec![1]
-        } else {
-            vec![]
-        }
-    } else {
-        let mut winning_charging_times = Vec::new();
-
-        for candidate in 1..race_time - 1 {
-            let distance_covered = calculate_distance(&race_time, &candidate);
-            if distance_covered > *record_distance {
-                winning_charging_times.push(candidate);
-            }
-        }
-
-        winning_charging_times
-    }
+    (1..*race_time)
+        .filter(|candidate| calculate_distance(race_time, candidate) > *record_distance)
+        .collect()
 }
 
 fn calculate_distance(race_time: &usize, charging_time: &usize) -> usize {
@@ -1,90 +1,119 @@
+use crate::arithmetic::{self, OverflowError};
+use crate::explain::{Column, Explanation};
+use crate::puzzle_reader;
 use std::{cmp::Ordering, collections::HashMap};
 
-pub fn calculate_total_winning(file: &str) -> usize {
+pub fn calculate_total_winning(file: &str) -> Result<u128, OverflowError> {
     let mut hands = parse_input(file);
-    hands.sort_by(|a, b| {
-        let (a_hand, _) = a;
-        let (b_hand, _) = b;
-        let ord = if std::mem::discriminant(a_hand) == std::mem::discriminant(b_hand) {
-            Ordering::Equal
-        } else {
-            a_hand.partial_cmp(b_hand).unwrap_or(Ordering::Equal)
-        };
-        if ord == Ordering::Equal {
-            let mut card_ord = Ordering::Equal;
-            let cards_zipped = a_hand
-                .get_cards()
-                .iter()
-                .zip(b_hand.get_cards().iter())
-                .collect::<Vec<(&Card, &Card)>>();
-            for (card_a, card_b) in cards_zipped {
-                card_ord = card_b.partial_cmp(&card_a).unwrap_or(Ordering::Equal);
-                if card_ord != Ordering::Equal {
-                    break;
-                }
-            }
-            card_ord
-        } else {
-            ord
-        }
+    hands.sort_by(|(a_hand, _), (b_hand, _)| compare_hands(a_hand, b_hand));
+    total_winnings(&hands)
+}
+
+pub fn calculate_total_winning_with_jokers(file: &str) -> Result<u128, OverflowError> {
+    let mut hands = parse_input_with_joker(file);
+    hands.sort_by(|(a_hand, _), (b_hand, _)| compare_hands_with_jokers(a_hand, b_hand));
+    total_winnings(&hands)
+}
+
+// The hands come sorted from the strongest, which gets the highest rank
+fn total_winnings<T>(hands: &[(T, usize)]) -> Result<u128, OverflowError> {
+    hands.iter().enumerate().try_fold(0, |sum, (i, (_, bet))| {
+        let strength = (hands.len() - i) as u128;
+        let winnings = arithmetic::mul(strength, *bet as u128, "total winnings")?;
+        arithmetic::add(sum, winnings, "total winnings")
+    })
+}
+
+// Lists the hands from the weakest, which gets rank 1, up to the strongest
+pub fn explain_total_winning(file: &str, with_jokers: bool) -> Explanation {
+    let mut hands = if with_jokers {
+        parse_input_with_joker(file)
+    } else {
+        parse_input(file)
+    };
+    hands.sort_by(|(a_hand, _), (b_hand, _)| match with_jokers {
+        true => compare_hands_with_jokers(a_hand, b_hand),
+        false => compare_hands(a_hand, b_hand),
     });
-    let mut sum = 0;
-    for (i, hand) in hands.iter().enumerate() {
-        println!("{:?}", hand);
-        let (_, bet) = hand;
-        let strength = hands.len() - i;
-        sum += strength * bet;
+
+    let mut explanation = Explanation::new(vec![
+        Column::number("rank"),
+        Column::text("hand"),
+        Column::text("type"),
+        Column::number("bid"),
+        Column::number("winnings"),
+    ]);
+    for (rank, (hand, bet)) in hands.iter().rev().enumerate().map(|(i, hand)| (i + 1, hand)) {
+        explanation.add_row(vec![
+            rank.to_string(),
+            hand.get_cards().iter().map(|card| card.symbol()).collect(),
+            hand.kind_name().to_string(),
+            bet.to_string(),
+            (rank * bet).to_string(),
+        ]);
     }
 
-    sum
+    explanation
 }
 
-pub fn calculate_total_winning_with_jokers(file: &str) -> usize {
-    let mut hands = parse_input_with_joker(file);
-    hands.sort_by(|a, b| {
-        let (a_hand, _) = a;
-        let (b_hand, _) = b;
-        let ord = if std::mem::discriminant(a_hand) == std::mem::discriminant(b_hand) {
-            Ordering::Equal
-        } else {
-            a_hand.partial_cmp(b_hand).unwrap_or(Ordering::Equal)
-        };
-        if ord == Ordering::Equal {
-            let mut card_ord = Ordering::Equal;
-            let cards_zipped = a_hand
-                .get_cards()
-                .iter()
-                .zip(b_hand.get_cards().iter())
-                .collect::<Vec<(&Card, &Card)>>();
-            for (card_a, card_b) in cards_zipped {
-                card_ord = match (card_a, card_b) {
-                    (Card::Jack, Card::Jack) => Ordering::Equal,
-                    (Card::Jack, _) => Ordering::Greater,
-                    (_, Card::Jack) => Ordering::Less,
-                    _ => card_b.partial_cmp(&card_a).unwrap_or(Ordering::Equal)
-                };
-                if card_ord != Ordering::Equal {
-                    break;
-                }
+// Sorts the strongest hands first
+fn compare_hands(a_hand: &Hand, b_hand: &Hand) -> Ordering {
+    let ord = if std::mem::discriminant(a_hand) == std::mem::discriminant(b_hand) {
+        Ordering::Equal
+    } else {
+        a_hand.partial_cmp(b_hand).unwrap_or(Ordering::Equal)
+    };
+    if ord == Ordering::Equal {
+        let mut card_ord = Ordering::Equal;
+        let cards_zipped = a_hand
+            .get_cards()
+            .iter()
+            .zip(b_hand.get_cards().iter())
+            .collect::<Vec<(&Card, &Card)>>();
+        for (card_a, card_b) in cards_zipped {
+            card_ord = card_b.partial_cmp(card_a).unwrap_or(Ordering::Equal);
+            if card_ord != Ordering::Equal {
+                break;
             }
-            card_ord
-        } else {
-            ord
         }
-    });
-    let mut sum = 0;
-    for (i, hand) in hands.iter().enumerate() {
-        println!("{:?}", hand);
-        let (_, bet) = hand;
-        let strength = hands.len() - i;
-        sum += strength * bet;
+        card_ord
+    } else {
+        ord
     }
+}
 
-    sum
+fn compare_hands_with_jokers(a_hand: &Hand, b_hand: &Hand) -> Ordering {
+    let ord = if std::mem::discriminant(a_hand) == std::mem::discriminant(b_hand) {
+        Ordering::Equal
+    } else {
+        a_hand.partial_cmp(b_hand).unwrap_or(Ordering::Equal)
+    };
+    if ord == Ordering::Equal {
+        let mut card_ord = Ordering::Equal;
+        let cards_zipped = a_hand
+            .get_cards()
+            .iter()
+            .zip(b_hand.get_cards().iter())
+            .collect::<Vec<(&Card, &Card)>>();
+        for (card_a, card_b) in cards_zipped {
+            card_ord = match (card_a, card_b) {
+                (Card::Jack, Card::Jack) => Ordering::Equal,
+                (Card::Jack, _) => Ordering::Greater,
+                (_, Card::Jack) => Ordering::Less,
+                _ => card_b.partial_cmp(card_a).unwrap_or(Ordering::Equal)
+            };
+            if card_ord != Ordering::Equal {
+                break;
+            }
+        }
+        card_ord
+    } else {
+        ord
+    }
 }
 
 fn parse_input(file: &str) -> Vec<(Hand, usize)> {
-    file.split("\n")
+    puzzle_reader::lines(file)
         .filter_map(|line| {
             let mut line_split = line.split_whitespace();
             let maybe_hand = line_split.next().and_then(Hand::parse);
@@ -99,7 +128,7 @@ fn parse_input(file: &str) -> Vec<(Hand, usize)> {
 }
 
 fn parse_input_with_joker(file: &str) -> Vec<(Hand, usize)> {
-    file.split("\n")
+    puzzle_reader::lines(file)
         .filter_map(|line| {
             let mut line_split = line.split_whitespace();
             let maybe_hand = line_split.next().and_then(Hand::parse_with_jokers);
@@ -113,7 +142,7 @@ fn parse_input_with_joker(file: &str) -> Vec<(Hand, usize)> {
         .collect()
 }
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
-enum Card {
+pub enum Card {
     Ace = 14,
     King = 13,
     Queen = 12,
@@ -148,10 +177,28 @@ impl Card {
             _ => None,
         }
     }
+
+    pub fn symbol(&self) -> char {
+        match self {
+            Card::Two => '2',
+            Card::Three => '3',
+            Card::Four => '4',
+            Card::Five => '5',
+            Card::Six => '6',
+            Card::Seven => '7',
+            Card::Eight => '8',
+            Card::Nine => '9',
+            Card::Ten => 'T',
+            Card::Jack => 'J',
+            Card::Queen => 'Q',
+            Card::King => 'K',
+            Card::Ace => 'A',
+        }
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Ord, PartialOrd)]
-enum Hand {
+pub enum Hand {
     FiveOfAKind { cards: Vec<Card> },
     FourOfAKind { cards: Vec<Card> },
     FullHouse { cards: Vec<Card> },
@@ -177,6 +224,18 @@ impl Hand {
         Hand::from_cards_with_joker(&cards)
     }
 
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            Hand::FiveOfAKind { .. } => "five of a kind",
+            Hand::FourOfAKind { .. } => "four of a kind",
+            Hand::FullHouse { .. } => "full house",
+            Hand::ThreeOfAKind { .. } => "three of a kind",
+            Hand::TwoPair { .. } => "two pair",
+            Hand::OnePair { .. } => "one pair",
+            Hand::HighCard { .. } => "high card",
+        }
+    }
+
     pub fn get_cards(&self) -> &Vec<Card> {
         match self {
             Hand::FiveOfAKind { cards }
@@ -0,0 +1,563 @@
+use crate::config::{Config, OutputFormat};
+use crate::{json, registry};
+
+const PROGRAM: &str = "aoc2023";
+const HELP_FLAG: &str = "--help";
+const LIST_FLAG: &str = "--list";
+
+pub const RUN_COMMAND: &str = "run";
+pub const BENCH_COMMAND: &str = "bench";
+pub const VERIFY_COMMAND: &str = "verify";
+pub const LIST_COMMAND: &str = "list";
+pub const GENERATE_COMMAND: &str = "generate";
+pub const DIFFTEST_COMMAND: &str = "difftest";
+pub const FUZZ_COMMAND: &str = "fuzz";
+pub const CONFIG_COMMAND: &str = "config";
+pub const COMPLETIONS_COMMAND: &str = "completions";
+pub const SERVE_COMMAND: &str = "serve";
+pub const FETCH_COMMAND: &str = "fetch";
+pub const SUBMIT_COMMAND: &str = "submit";
+pub const STUB_COMMAND: &str = "stub";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FlagKind {
+    Switch,
+    Number,
+    Text(&'static str),
+}
+
+struct Flag {
+    name: &'static str,
+    kind: FlagKind,
+    help: &'static str,
+}
+
+pub struct Command {
+    pub name: &'static str,
+    summary: &'static str,
+    positionals: &'static [&'static str],
+    flags: &'static [Flag],
+}
+
+const fn flag(name: &'static str, kind: FlagKind, help: &'static str) -> Flag {
+    Flag { name, kind, help }
+}
+
+const DAY_FLAG: Flag = flag("--day", FlagKind::Number, "Day of the puzzle");
+const PART_FLAG: Flag = flag("--part", FlagKind::Number, "Part of the puzzle");
+const SEED_FLAG: Flag = flag("--seed", FlagKind::Number, "Seed of the random generator");
+const VOCABULARY_FLAG: Flag = flag(
+    "--vocabulary",
+    FlagKind::Text("LANG[,LANG]"),
+    "Spelled digits of day 1: english, german, french, spanish, polish or none",
+);
+const VOCABULARY_FILE_FLAG: Flag = flag(
+    "--vocabulary-file",
+    FlagKind::Text("PATH"),
+    "Extra `word = value` lines for the spelled digits of day 1",
+);
+const BAG_FLAG: Flag = flag(
+    "--bag",
+    FlagKind::Text("COLOR=N[,COLOR=N]"),
+    "Cubes in the bag of day 2",
+);
+const BAG_FILE_FLAG: Flag = flag(
+    "--bag-file",
+    FlagKind::Text("PATH"),
+    "File with `color = count` lines for the bag of day 2",
+);
+
+// Every command accepts these, they override the values from `aoc.toml`
+const CONFIG_FLAGS: &[Flag] = &[
+    flag("--year", FlagKind::Number, "Year of the puzzles"),
+    flag(
+        "--input-dir",
+        FlagKind::Text("PATH"),
+        "Directory with the puzzle inputs",
+    ),
+    flag("--format", FlagKind::Text("text|json"), "Output format"),
+    flag(
+        "--verbosity",
+        FlagKind::Number,
+        "0 prints just the answers, 2 adds timings",
+    ),
+    flag(
+        "--iterations",
+        FlagKind::Number,
+        "Iterations of a benchmark",
+    ),
+    flag(
+        "--set",
+        FlagKind::Text("KEY=VALUE"),
+        "Any other configuration value, can be repeated",
+    ),
+    flag(HELP_FLAG, FlagKind::Switch, "Print the help of the command"),
+];
+
+const COMMANDS: &[Command] = &[
+    Command {
+        name: RUN_COMMAND,
+        summary: "Solve a puzzle, the default when the first argument is a flag",
+        positionals: &[],
+        flags: &[
+            DAY_FLAG,
+            PART_FLAG,
+            flag("--render", FlagKind::Switch, "Draw the puzzle grid"),
+            flag(
+                "--explain",
+                FlagKind::Switch,
+                "Show the steps leading to the answer",
+            ),
+            flag(
+                "--report",
+                FlagKind::Switch,
+                "List the parts of the input that look suspicious",
+            ),
+            flag(
+                "--stream",
+                FlagKind::Switch,
+                "Read the input line by line instead of loading it whole",
+            ),
+            flag(
+                "--export",
+                FlagKind::Text("PATH"),
+                "Export the puzzle as SVG or PPM",
+            ),
+            VOCABULARY_FLAG,
+            VOCABULARY_FILE_FLAG,
+            BAG_FLAG,
+            BAG_FILE_FLAG,
+        ],
+    },
+    Command {
+        name: BENCH_COMMAND,
+        summary: "Time the solution of a puzzle",
+        positionals: &[],
+        flags: &[
+            DAY_FLAG,
+            PART_FLAG,
+            VOCABULARY_FLAG,
+            VOCABULARY_FILE_FLAG,
+            BAG_FLAG,
+            BAG_FILE_FLAG,
+        ],
+    },
+    Command {
+        name: VERIFY_COMMAND,
+        summary: "Check the answers against the answer_N files next to the inputs",
+        positionals: &[],
+        flags: &[DAY_FLAG],
+    },
+    Command {
+        name: LIST_COMMAND,
+        summary: "List the puzzles with their solved parts",
+        positionals: &[],
+        flags: &[],
+    },
+    Command {
+        name: GENERATE_COMMAND,
+        summary: "Print a random input for a day",
+        positionals: &[],
+        flags: &[
+            DAY_FLAG,
+            flag("--size", FlagKind::Number, "Rough size of the input"),
+            SEED_FLAG,
+        ],
+    },
+    Command {
+        name: DIFFTEST_COMMAND,
+        summary: "Compare solvers with brute force references on random inputs",
+        positionals: &[],
+        flags: &[
+            DAY_FLAG,
+            flag("--runs", FlagKind::Number, "Random inputs per solver"),
+            SEED_FLAG,
+            flag(
+                "--bench",
+                FlagKind::Number,
+                "Time both solvers on inputs doubling in size up to this one instead",
+            ),
+        ],
+    },
+    Command {
+        name: FUZZ_COMMAND,
+        summary: "Feed mutated inputs to the parsers",
+        positionals: &[],
+        flags: &[
+            flag(
+                "--target",
+                FlagKind::Text("NAME"),
+                "Parser to fuzz, all by default",
+            ),
+            flag("--runs", FlagKind::Number, "Mutated inputs per parser"),
+            SEED_FLAG,
+        ],
+    },
+    Command {
+        name: SERVE_COMMAND,
+        summary: "Serve the solvers as a JSON API on localhost",
+        positionals: &[],
+        flags: &[flag("--port", FlagKind::Number, "Port to listen on")],
+    },
+    Command {
+        name: FETCH_COMMAND,
+        summary: "Download the input of a day from base_url into the input directory",
+        positionals: &[],
+        flags: &[
+            DAY_FLAG,
+            flag(
+                "--force",
+                FlagKind::Switch,
+                "Download even if the input is cached",
+            ),
+        ],
+    },
+    Command {
+        name: SUBMIT_COMMAND,
+        summary: "Send the answer of a part to base_url and print the verdict",
+        positionals: &[],
+        flags: &[
+            DAY_FLAG,
+            PART_FLAG,
+            flag(
+                "--answer",
+                FlagKind::Text("ANSWER"),
+                "Answer to send instead of solving the puzzle",
+            ),
+        ],
+    },
+    Command {
+        name: STUB_COMMAND,
+        summary: "Serve a stand-in for the puzzle site to try fetch and submit against",
+        positionals: &[],
+        flags: &[flag("--port", FlagKind::Number, "Port to listen on")],
+    },
+    Command {
+        name: CONFIG_COMMAND,
+        summary: "Print the effective configuration",
+        positionals: &["show"],
+        flags: &[],
+    },
+    Command {
+        name: COMPLETIONS_COMMAND,
+        summary: "Print a shell completion script",
+        positionals: &["bash", "zsh", "fish"],
+        flags: &[],
+    },
+];
+
+pub enum Invocation<'a> {
+    Help(String),
+    Command(&'static Command, &'a [String]),
+}
+
+pub fn parse(program_args: &[String]) -> Result<Invocation<'_>, String> {
+    let args = program_args.get(1..).unwrap_or_default();
+
+    let (command, command_args) = match args.first().map(|arg| arg.as_str()) {
+        None | Some(HELP_FLAG) => return Ok(Invocation::Help(usage())),
+        Some(LIST_FLAG) => (find_command(LIST_COMMAND)?, &args[1..]),
+        // `--day=1 --part=2` keeps working without spelling out `run`
+        Some(arg) if arg.starts_with("--") => (find_command(RUN_COMMAND)?, args),
+        Some(name) => (find_command(name)?, &args[1..]),
+    };
+
+    if command_args.iter().any(|arg| arg == HELP_FLAG) {
+        return Ok(Invocation::Help(command_help(command)));
+    }
+
+    validate(command, command_args)?;
+    Ok(Invocation::Command(command, command_args))
+}
+
+fn find_command(name: &str) -> Result<&'static Command, String> {
+    COMMANDS
+        .iter()
+        .find(|command| command.name == name)
+        .ok_or(format!(
+            "Unknown command '{name}', see {PROGRAM} {HELP_FLAG}"
+        ))
+}
+
+fn all_flags(command: &Command) -> impl Iterator<Item = &Flag> {
+    command.flags.iter().chain(CONFIG_FLAGS.iter())
+}
+
+fn validate(command: &Command, args: &[String]) -> Result<(), String> {
+    let mut positionals = args.iter().filter(|arg| !arg.starts_with("--"));
+
+    if let Some(positional) = positionals.next() {
+        if !command.positionals.contains(&positional.as_str()) {
+            return Err(format!(
+                "Unexpected argument '{positional}' for {}, see {PROGRAM} {} {HELP_FLAG}",
+                command.name, command.name
+            ));
+        }
+    }
+    if let Some(positional) = positionals.next() {
+        return Err(format!(
+            "Unexpected argument '{positional}' for {}",
+            command.name
+        ));
+    }
+
+    for arg in args.iter().filter(|arg| arg.starts_with("--")) {
+        let (name, value) = match arg.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (arg.as_str(), None),
+        };
+
+        let flag = all_flags(command)
+            .find(|flag| flag.name == name)
+            .ok_or(format!(
+                "Unknown flag '{name}' for {}, see {PROGRAM} {} {HELP_FLAG}",
+                command.name, command.name
+            ))?;
+
+        match (flag.kind, value) {
+            (FlagKind::Switch, None) => {}
+            (FlagKind::Switch, Some(_)) => return Err(format!("{name} does not take a value")),
+            (_, None) | (_, Some("")) => return Err(format!("Missing value for {name}=")),
+            (FlagKind::Number, Some(value)) if value.parse::<usize>().is_err() => {
+                return Err(format!("Invalid value for {arg}, expected a number"))
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn usage() -> String {
+    let width = COMMANDS
+        .iter()
+        .map(|command| command.name.len())
+        .max()
+        .unwrap_or(0);
+    let commands = COMMANDS
+        .iter()
+        .map(|command| format!("  {:width$}  {}", command.name, command.summary))
+        .collect::<Vec<String>>();
+
+    format!(
+        "Usage: {PROGRAM} <command> [flags]\n\nCommands:\n{}\n\n\
+        Run `{PROGRAM} <command> {HELP_FLAG}` for the flags of a command \
+        or `{PROGRAM} {LIST_FLAG}` for the puzzles.",
+        commands.join("\n")
+    )
+}
+
+fn command_help(command: &Command) -> String {
+    let positionals = match command.positionals {
+        [] => String::new(),
+        values => format!(" <{}>", values.join("|")),
+    };
+
+    format!(
+        "Usage: {PROGRAM} {}{positionals} [flags]\n{}\n\nFlags:\n{}\n\nConfiguration flags:\n{}",
+        command.name,
+        command.summary,
+        flags_help(command.flags),
+        flags_help(CONFIG_FLAGS)
+    )
+}
+
+fn flags_help(flags: &[Flag]) -> String {
+    if flags.is_empty() {
+        return "  none".to_string();
+    }
+
+    let usages = flags.iter().map(flag_usage).collect::<Vec<String>>();
+    let width = usages.iter().map(|usage| usage.len()).max().unwrap_or(0);
+
+    usages
+        .iter()
+        .zip(flags.iter())
+        .map(|(usage, flag)| format!("  {usage:width$}  {}", flag.help))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn flag_usage(flag: &Flag) -> String {
+    match flag.kind {
+        FlagKind::Switch => flag.name.to_string(),
+        FlagKind::Number => format!("{}=N", flag.name),
+        FlagKind::Text(value) => format!("{}={}", flag.name, value),
+    }
+}
+
+pub fn list(config: &Config) -> String {
+    let days = registry::days(config.year);
+
+    match config.format {
+        OutputFormat::Text if days.is_empty() => format!("No puzzles for {} yet", config.year),
+        OutputFormat::Text => days
+            .iter()
+            .map(|day| {
+                let parts = match day.parts.as_slice() {
+                    [] => "not solved yet".to_string(),
+                    parts => {
+                        let parts = parts
+                            .iter()
+                            .map(|part| part.to_string())
+                            .collect::<Vec<String>>();
+                        format!("parts {}", parts.join(", "))
+                    }
+                };
+                format!("Day {:2}: {:32} {}", day.day, day.title, parts)
+            })
+            .collect::<Vec<String>>()
+            .join("\n"),
+        OutputFormat::Json => days_json(config.year),
+    }
+}
+
+pub fn days_json(year: usize) -> String {
+    let days = registry::days(year)
+        .iter()
+        .map(|day| {
+            let parts = day
+                .parts
+                .iter()
+                .map(|part| part.to_string())
+                .collect::<Vec<String>>();
+            json::object(&[
+                ("day", day.day.to_string()),
+                ("title", json::string(day.title)),
+                ("parts", format!("[{}]", parts.join(","))),
+            ])
+        })
+        .collect::<Vec<String>>();
+
+    format!("[{}]", days.join(","))
+}
+
+pub fn completions(shell: &str) -> Result<String, String> {
+    match shell {
+        "bash" => Ok(bash_completions()),
+        "zsh" => Ok(zsh_completions()),
+        "fish" => Ok(fish_completions()),
+        _ => Err(format!(
+            "Unknown shell '{shell}', expected bash, zsh or fish"
+        )),
+    }
+}
+
+// Value flags are completed up to their `=` so the value can be typed right after
+fn completion_words(command: &Command) -> Vec<String> {
+    command
+        .positionals
+        .iter()
+        .map(|positional| positional.to_string())
+        .chain(all_flags(command).map(|flag| match flag.kind {
+            FlagKind::Switch => flag.name.to_string(),
+            _ => format!("{}=", flag.name),
+        }))
+        .collect()
+}
+
+fn command_names() -> String {
+    COMMANDS
+        .iter()
+        .map(|command| command.name)
+        .chain([HELP_FLAG, LIST_FLAG])
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+fn bash_completions() -> String {
+    let cases = COMMANDS
+        .iter()
+        .map(|command| {
+            format!(
+                "            {}) words=\"{}\" ;;",
+                command.name,
+                completion_words(command).join(" ")
+            )
+        })
+        .collect::<Vec<String>>();
+
+    format!(
+        "_{PROGRAM}() {{\n\
+        \x20   local cur=\"${{COMP_WORDS[COMP_CWORD]}}\" words\n\
+        \x20   if [ \"$COMP_CWORD\" -eq 1 ]; then\n\
+        \x20       words=\"{}\"\n\
+        \x20   else\n\
+        \x20       case \"${{COMP_WORDS[1]}}\" in\n{}\n\
+        \x20           *) words=\"{}\" ;;\n\
+        \x20       esac\n\
+        \x20   fi\n\
+        \x20   COMPREPLY=($(compgen -W \"$words\" -- \"$cur\"))\n\
+        \x20   [[ \"${{COMPREPLY[0]}}\" == *= ]] && compopt -o nospace\n\
+        }}\n\
+        complete -F _{PROGRAM} {PROGRAM}",
+        command_names(),
+        cases.join("\n"),
+        completion_words(find_command(RUN_COMMAND).unwrap_or(&COMMANDS[0])).join(" ")
+    )
+}
+
+fn zsh_completions() -> String {
+    let cases = COMMANDS
+        .iter()
+        .map(|command| {
+            let (values, switches): (Vec<String>, Vec<String>) = completion_words(command)
+                .into_iter()
+                .partition(|word| word.ends_with('='));
+            format!(
+                "        {}) compadd -S '' -- {}; compadd -- {} ;;",
+                command.name,
+                values.join(" "),
+                switches.join(" ")
+            )
+        })
+        .collect::<Vec<String>>();
+
+    format!(
+        "#compdef {PROGRAM}\n\
+        _{PROGRAM}() {{\n\
+        \x20   if (( CURRENT == 2 )); then\n\
+        \x20       compadd -- {}\n\
+        \x20       return\n\
+        \x20   fi\n\
+        \x20   case \"$words[2]\" in\n{}\n\
+        \x20   esac\n\
+        }}\n\
+        compdef _{PROGRAM} {PROGRAM}",
+        command_names(),
+        cases.join("\n")
+    )
+}
+
+fn fish_completions() -> String {
+    let mut lines = vec![format!("complete -c {PROGRAM} -f")];
+
+    for command in COMMANDS {
+        lines.push(format!(
+            "complete -c {PROGRAM} -n __fish_use_subcommand -a {} -d '{}'",
+            command.name, command.summary
+        ));
+
+        let condition = format!("'__fish_seen_subcommand_from {}'", command.name);
+        if !command.positionals.is_empty() {
+            lines.push(format!(
+                "complete -c {PROGRAM} -n {condition} -a '{}'",
+                command.positionals.join(" ")
+            ));
+        }
+        for flag in all_flags(command) {
+            let requires_value = if flag.kind == FlagKind::Switch {
+                ""
+            } else {
+                " -r"
+            };
+            lines.push(format!(
+                "complete -c {PROGRAM} -n {condition} -l {}{requires_value} -d '{}'",
+                flag.name.trim_start_matches("--"),
+                flag.help
+            ));
+        }
+    }
+
+    lines.join("\n")
+}
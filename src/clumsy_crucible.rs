@@ -1,15 +1,8 @@
+use crate::puzzle_reader;
 use std::collections::{BinaryHeap, HashMap, HashSet};
 
 type NodeAddress = (usize, usize);
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct Node {
-    coordinates: NodeAddress,
-    heat_loss_factor: u8,
-    shortest_distance: usize,
-    previous_node: Option<NodeAddress>,
-}
-
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct State {
     node_address: NodeAddress,
@@ -48,11 +41,21 @@ impl PartialOrd for State {
 }
 
 pub fn find_shortest_path(file: &str) -> Option<usize> {
-    let (nodes, mut nodes_map) = parse_input(file);
+    find_best_route(file, false).map(|(heat_loss, _)| heat_loss)
+}
+
+pub fn find_shortest_path2(file: &str) -> Option<usize> {
+    find_best_route(file, true).map(|(heat_loss, _)| heat_loss)
+}
+
+// Returns the minimal heat loss together with the blocks the crucible goes through, starting with
+// the top-left one
+pub fn find_best_route(file: &str, ultra_crucible: bool) -> Option<(usize, Vec<NodeAddress>)> {
+    let nodes = parse_input(file);
 
     let start_node_address: NodeAddress = (0, 0);
     let rows_number = nodes.len();
-    let columns_number = nodes.first().unwrap().len();
+    let columns_number = nodes.first()?.len();
     let end_node_address: NodeAddress = (rows_number - 1, columns_number - 1);
 
     let mut heap = BinaryHeap::new();
@@ -64,15 +67,16 @@ pub fn find_shortest_path(file: &str) -> Option<usize> {
         moves_in_direction: 0,
     });
 
-    let mut results: Vec<State> = Vec::new();
     let mut seen: HashSet<String> = HashSet::new();
+    let mut lowest_heat_loss: HashMap<String, usize> = HashMap::new();
+    let mut previous_states: HashMap<String, State> = HashMap::new();
 
     while let Some(current_state) = heap.pop() {
-        //println!("State: {:?}", current_state);
-
-        if current_state.node_address == end_node_address {
-            results.push(current_state);
-            continue;
+        // An ultra crucible can only stop at the factory after four blocks in a straight line
+        let can_stop = !ultra_crucible || current_state.moves_in_direction >= 4;
+        if current_state.node_address == end_node_address && can_stop {
+            let route = build_route(&previous_states, &current_state);
+            return Some((current_state.accumulated_heat_loss, route));
         }
 
         let seen_key = build_seen_key(&current_state);
@@ -84,139 +88,59 @@ pub fn find_shortest_path(file: &str) -> Option<usize> {
 
         let neighbours = if current_state.node_address == start_node_address {
             vec![(Direction::Right, (0, 1)), (Direction::Down, (1, 0))]
+        } else if ultra_crucible {
+            get_possible_neighbours2(&current_state, &rows_number, &columns_number)
         } else {
             get_possible_neighbours(&current_state, &rows_number, &columns_number)
         };
 
-        //println!("Checking neighbours: {:?}", neighbours);
-        for neighbour in neighbours.iter() {
-            let neighbour_data = nodes_map.get_mut(&build_key(neighbour.1)).unwrap();
-            //println!("Current neighbour data: {:?}", neighbour_data);
+        for (direction, (row, column)) in neighbours.into_iter() {
+            let Some(heat_loss_factor) = nodes.get(row).and_then(|r| r.get(column)) else {
+                continue;
+            };
 
             let next = State {
-                node_address: neighbour_data.coordinates,
+                node_address: (row, column),
                 accumulated_heat_loss: current_state.accumulated_heat_loss
-                    + neighbour_data.heat_loss_factor as usize,
-                direction: neighbour.0,
-                moves_in_direction: if neighbour.0 == current_state.direction {
+                    + *heat_loss_factor as usize,
+                direction,
+                moves_in_direction: if direction == current_state.direction {
                     current_state.moves_in_direction + 1
                 } else {
                     1
                 },
             };
 
-            //println!("Next: {:?}", next);
             let neighbour_seen_key = build_seen_key(&next);
+            let is_improvement = lowest_heat_loss
+                .get(&neighbour_seen_key)
+                .map(|heat_loss| next.accumulated_heat_loss < *heat_loss)
+                .unwrap_or(true);
 
-            if seen.contains(&neighbour_seen_key) {
+            if seen.contains(&neighbour_seen_key) || !is_improvement {
                 continue;
-            } else {
-                heap.push(next.clone());
             }
 
-            if next.accumulated_heat_loss < neighbour_data.shortest_distance {
-                //println!("Updating path");
-                neighbour_data.shortest_distance = next.accumulated_heat_loss;
-                neighbour_data.previous_node = Some(current_state.node_address);
-            }
-            //println!("\n");
+            lowest_heat_loss.insert(neighbour_seen_key.clone(), next.accumulated_heat_loss);
+            previous_states.insert(neighbour_seen_key, current_state.clone());
+            heap.push(next);
         }
-
-        //println!("Heap at the end: {:?}", heap);
-        //println!("\n");
     }
 
-    //println!("Results {:?}", results);
-
-    nodes_map
-        .get(&build_key(end_node_address))
-        .map(|r| r.shortest_distance)
+    None
 }
 
-pub fn find_shortest_path2(file: &str) -> Option<usize> {
-    let (nodes, mut nodes_map) = parse_input(file);
+fn build_route(previous_states: &HashMap<String, State>, end_state: &State) -> Vec<NodeAddress> {
+    let mut route = vec![end_state.node_address];
+    let mut current_state = end_state;
 
-    let start_node_address: NodeAddress = (0, 0);
-    let rows_number = nodes.len();
-    let columns_number = nodes.first().unwrap().len();
-    let end_node_address: NodeAddress = (rows_number - 1, columns_number - 1);
-
-    let mut heap = BinaryHeap::new();
-
-    heap.push(State {
-        node_address: start_node_address,
-        accumulated_heat_loss: 0,
-        direction: Direction::Up,
-        moves_in_direction: 0,
-    });
-
-    let mut results: Vec<State> = Vec::new();
-    let mut seen: HashSet<String> = HashSet::new();
-
-    while let Some(current_state) = heap.pop() {
-        //println!("State: {:?}", current_state);
-
-        if current_state.node_address == end_node_address {
-            results.push(current_state);
-            continue;
-        }
-
-        let seen_key = build_seen_key(&current_state);
-        if seen.contains(&seen_key) {
-            continue;
-        } else {
-            seen.insert(seen_key);
-        }
-
-        let neighbours = if current_state.node_address == start_node_address {
-            vec![(Direction::Right, (0, 1)), (Direction::Down, (1, 0))]
-        } else {
-            get_possible_neighbours2(&current_state, &rows_number, &columns_number)
-        };
-
-        //println!("Checking neighbours: {:?}", neighbours);
-        for neighbour in neighbours.iter() {
-            let neighbour_data = nodes_map.get_mut(&build_key(neighbour.1)).unwrap();
-            //println!("Current neighbour data: {:?}", neighbour_data);
-
-            let next = State {
-                node_address: neighbour_data.coordinates,
-                accumulated_heat_loss: current_state.accumulated_heat_loss
-                    + neighbour_data.heat_loss_factor as usize,
-                direction: neighbour.0,
-                moves_in_direction: if neighbour.0 == current_state.direction {
-                    current_state.moves_in_direction + 1
-                } else {
-                    1
-                },
-            };
-
-            //println!("Next: {:?}", next);
-            let neighbour_seen_key = build_seen_key(&next);
-
-            if seen.contains(&neighbour_seen_key) {
-                continue;
-            } else {
-                heap.push(next.clone());
-            }
-
-            if next.accumulated_heat_loss < neighbour_data.shortest_distance {
-                //println!("Updating path");
-                neighbour_data.shortest_distance = next.accumulated_heat_loss;
-                neighbour_data.previous_node = Some(current_state.node_address);
-            }
-            //println!("\n");
-        }
-
-        //println!("Heap at the end: {:?}", heap);
-        //println!("\n");
+    while let Some(previous_state) = previous_states.get(&build_seen_key(current_state)) {
+        route.push(previous_state.node_address);
+        current_state = previous_state;
     }
 
-    //println!("Results {:?}", results);
-
-    nodes_map
-        .get(&build_key(end_node_address))
-        .map(|r| r.shortest_distance)
+    route.reverse();
+    route
 }
 
 fn build_seen_key(state: &State) -> String {
@@ -378,52 +302,37 @@ fn move_in_direction(
     }
 }
 
-fn arrived_from(current_node: &NodeAddress, prev_node: &NodeAddress) -> Direction {
-    if current_node.0 == prev_node.0 {
-        if current_node.1 > prev_node.1 {
-            Direction::Right
-        } else {
-            Direction::Left
-        }
-    } else if current_node.1 == prev_node.1 {
-        if current_node.0 > prev_node.0 {
-            Direction::Down
-        } else {
-            Direction::Up
-        }
-    } else {
-        panic!("illegal situation");
-    }
+pub fn get_heat_loss_map(file: &str) -> Vec<Vec<u8>> {
+    parse_input(file)
 }
 
-fn parse_input(file: &str) -> (Vec<Vec<u8>>, HashMap<String, Node>) {
-    let mut nodes: Vec<Vec<u8>> = Vec::new();
-    let mut nodes_map: HashMap<String, Node> = HashMap::new();
-
-    let lines = file.split("\n").filter(|l| !l.is_empty());
-    for (i, line) in lines.enumerate() {
-        let mut numbers: Vec<u8> = Vec::new();
-
-        for (j, c) in line.chars().enumerate() {
-            let num = c.to_string().parse::<u8>().unwrap();
-            let key = build_key((i, j));
-            let node = Node {
-                coordinates: (i, j),
-                heat_loss_factor: num,
-                shortest_distance: if i == 0 && j == 0 { 0 } else { std::usize::MAX },
-                previous_node: None,
-            };
+fn parse_input(file: &str) -> Vec<Vec<u8>> {
+    puzzle_reader::lines(file)
+        .filter(|l| !l.is_empty())
+        .map(|line| {
+            line.chars()
+                .map(|c| c.to_string().parse::<u8>().unwrap())
+                .collect::<Vec<u8>>()
+        })
+        .collect()
+}
 
-            numbers.push(num);
-            nodes_map.insert(key, node);
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn an_ultra_crucible_does_not_stop_short_of_four_blocks() {
+        let city = "111111111111\n999999999991\n999999999991\n999999999991\n999999999991";
 
-        nodes.push(numbers);
+        assert_eq!(find_shortest_path2(city), Some(71));
     }
 
-    (nodes, nodes_map)
-}
+    #[test]
+    fn the_puzzle_example_is_solved() {
+        let city = "2413432311323\n3215453535623\n3255245654254\n3446585845452\n4546657867536\n1438598798454\n4457876987766\n3637877979653\n4654967986887\n4564679986453\n1224686865563\n2546548887735\n4322674655533";
 
-fn build_key(node_address: NodeAddress) -> String {
-    format!("{}-{}", node_address.0, node_address.1)
+        assert_eq!(find_shortest_path(city), Some(102));
+        assert_eq!(find_shortest_path2(city), Some(94));
+    }
 }
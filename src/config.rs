@@ -0,0 +1,400 @@
+use crate::puzzle_reader;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+const CONFIG_FILE_NAME: &str = "aoc.toml";
+const SET_ARG_PREFIX: &str = "--set=";
+const DEFAULT_INPUT_DIR: &str = "./input_data";
+const MAX_VERBOSITY: usize = 2;
+// There is no TLS client, the real site is only reachable through a local proxy in front of it
+const DEFAULT_BASE_URL: &str = "http://127.0.0.1:8024";
+const DEFAULT_SESSION_FILE: &str = "./.aoc_session";
+const DEFAULT_SUBMIT_INTERVAL: usize = 60;
+pub const SESSION_ENV_VAR: &str = "AOC_SESSION";
+
+// Command line flags overriding the configuration key next to them
+const FLAG_KEYS: [(&str, &str); 9] = [
+    ("--input-dir=", "input_dir"),
+    ("--year=", "year"),
+    ("--format=", "format"),
+    ("--verbosity=", "verbosity"),
+    ("--iterations=", "bench_iterations"),
+    ("--vocabulary=", "day_1.vocabulary"),
+    ("--vocabulary-file=", "day_1.vocabulary_file"),
+    ("--bag=", "day_2.bag"),
+    ("--bag-file=", "day_2.bag_file"),
+];
+
+// The parameters the registry reads from each `[day_N]` section, a trailing `*` stands for any suffix
+// like the color in `max_red`
+const DAY_KEYS: [(usize, &[&str]); 3] = [
+    (
+        1,
+        &[
+            "vocabulary",
+            "vocabulary_file",
+            "max_number",
+            "longest_match",
+        ],
+    ),
+    (2, &["bag", "bag_file", "max_*", "unknown_max", "at_least"]),
+    (3, &["gear_symbols", "gear_neighbours", "gear_aggregation"]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+impl OutputFormat {
+    fn parse(value: &str) -> Option<OutputFormat> {
+        match value {
+            "text" => Some(OutputFormat::Text),
+            "json" => Some(OutputFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+struct Entry {
+    value: String,
+    source: String,
+}
+
+// What the configuration is read from besides the command line. The default is an empty one, with
+// no `aoc.toml` and no session in the environment.
+#[derive(Default)]
+pub struct Environment {
+    pub config_file: Option<PathBuf>,
+    pub session: Option<String>,
+}
+
+impl Environment {
+    pub fn current() -> Environment {
+        Environment {
+            config_file: find_config_file(),
+            session: env::var(SESSION_ENV_VAR).ok(),
+        }
+    }
+}
+
+// The effective configuration: built-in defaults, overridden by the first `aoc.toml` found in the
+// working or home directory, overridden in turn by command line flags
+pub struct Config {
+    pub input_dir: String,
+    pub year: usize,
+    pub format: OutputFormat,
+    pub verbosity: usize,
+    pub bench_iterations: usize,
+    pub base_url: String,
+    pub session_file: String,
+    pub submit_interval: usize,
+    // Taken from `AOC_SESSION` and kept out of `entries`, so it is never shown
+    pub session: Option<String>,
+    entries: BTreeMap<String, Entry>,
+}
+
+impl Config {
+    pub fn load(program_args: &[String]) -> Result<Config, String> {
+        Config::load_with(Environment::current(), program_args)
+    }
+
+    pub fn load_with(environment: Environment, program_args: &[String]) -> Result<Config, String> {
+        let mut entries: BTreeMap<String, Entry> = BTreeMap::new();
+        let mut set = |key: &str, value: &str, source: &str| {
+            entries.insert(
+                key.to_string(),
+                Entry {
+                    value: value.to_string(),
+                    source: source.to_string(),
+                },
+            );
+        };
+
+        set("input_dir", DEFAULT_INPUT_DIR, "default");
+        set("year", &puzzle_reader::DEFAULT_YEAR.to_string(), "default");
+        set("format", "text", "default");
+        set("verbosity", "1", "default");
+        set("bench_iterations", "10", "default");
+        set("base_url", DEFAULT_BASE_URL, "default");
+        set("session_file", DEFAULT_SESSION_FILE, "default");
+        set(
+            "submit_interval",
+            &DEFAULT_SUBMIT_INTERVAL.to_string(),
+            "default",
+        );
+
+        if let Some(path) = environment.config_file {
+            let source = path.display().to_string();
+            let contents = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read {source}. Error was: {:?}", e))?;
+
+            for (key, value) in
+                parse_config_file(&contents).map_err(|e| format!("{source}: {e}"))?
+            {
+                set(&key, &value, &source);
+            }
+        }
+
+        for (flag, key) in FLAG_KEYS {
+            if let Some(value) = puzzle_reader::read_text_arg(program_args, flag) {
+                set(key, value, flag.trim_end_matches('='));
+            }
+        }
+
+        for assignment in program_args
+            .iter()
+            .filter_map(|arg| arg.strip_prefix(SET_ARG_PREFIX))
+        {
+            let (key, value) = assignment.split_once('=').ok_or(format!(
+                "Expected {SET_ARG_PREFIX}key=value, got '{assignment}'"
+            ))?;
+            set(key.trim(), value.trim(), "--set");
+        }
+
+        Config::from_entries(entries, environment.session)
+    }
+
+    fn from_entries(
+        entries: BTreeMap<String, Entry>,
+        session: Option<String>,
+    ) -> Result<Config, String> {
+        if let Some(key) = entries.keys().find(|key| !is_known_key(key)) {
+            return Err(format!("Unknown configuration key '{key}'"));
+        }
+
+        let value = |key: &str| entries[key].value.as_str();
+
+        let format = OutputFormat::parse(value("format")).ok_or(format!(
+            "Unknown output format '{}', expected text or json",
+            value("format")
+        ))?;
+
+        let verbosity = parse_number("verbosity", value("verbosity"))?;
+        if verbosity > MAX_VERBOSITY {
+            return Err(format!("Verbosity has to be between 0 and {MAX_VERBOSITY}"));
+        }
+
+        let bench_iterations = parse_number("bench_iterations", value("bench_iterations"))?;
+        if bench_iterations == 0 {
+            return Err("Benchmark iterations have to be at least 1".to_string());
+        }
+
+        let base_url = value("base_url").trim_end_matches('/');
+        if !base_url.starts_with("http://") {
+            return Err(format!(
+                "Unsupported base_url '{base_url}', expected http://host[:port]. HTTPS is not supported, reach https://adventofcode.com through a local TLS proxy and point base_url at it"
+            ));
+        }
+
+        Ok(Config {
+            input_dir: value("input_dir").to_string(),
+            year: parse_number("year", value("year"))?,
+            format,
+            verbosity,
+            bench_iterations,
+            base_url: base_url.to_string(),
+            session_file: value("session_file").to_string(),
+            submit_interval: parse_number("submit_interval", value("submit_interval"))?,
+            session,
+            entries,
+        })
+    }
+
+    // Per-day solver parameters are keyed `day_N.name`, which is what a `[day_N]` section gives
+    pub fn day_number_param(
+        &self,
+        day: usize,
+        name: &str,
+        default: usize,
+    ) -> Result<usize, String> {
+        let key = format!("day_{day}.{name}");
+        self.entries
+            .get(&key)
+            .map(|entry| parse_number(&key, &entry.value))
+            .unwrap_or(Ok(default))
+    }
+
+    pub fn day_text_param(&self, day: usize, name: &str) -> Option<&str> {
+        self.entries
+            .get(&format!("day_{day}.{name}"))
+            .map(|entry| entry.value.as_str())
+    }
+
+    // Every `day_N.*` key, without the prefix
+    pub fn day_params(&self, day: usize) -> Vec<(&str, &str)> {
+        let prefix = format!("day_{day}.");
+        self.entries
+            .iter()
+            .filter_map(|(key, entry)| {
+                key.strip_prefix(&prefix)
+                    .map(|name| (name, entry.value.as_str()))
+            })
+            .collect()
+    }
+
+    pub fn day_flag_param(&self, day: usize, name: &str, default: bool) -> Result<bool, String> {
+        match self.day_text_param(day, name) {
+            None => Ok(default),
+            Some("true") => Ok(true),
+            Some("false") => Ok(false),
+            Some(value) => Err(format!(
+                "Failed to read configuration value for 'day_{day}.{name}', expected true or false but got '{value}'"
+            )),
+        }
+    }
+
+    pub fn show(&self) -> String {
+        self.entries
+            .iter()
+            .map(|(key, entry)| format!("{key} = {}  # {}", entry.value, entry.source))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+fn is_known_key(key: &str) -> bool {
+    let is_day_param = key
+        .strip_prefix("day_")
+        .and_then(|rest| rest.split_once('.'))
+        .and_then(|(day, name)| Some((day.parse::<usize>().ok()?, name)))
+        .map(|(day, name)| is_day_param(day, name))
+        .unwrap_or(false);
+
+    is_day_param
+        || [
+            "input_dir",
+            "year",
+            "format",
+            "verbosity",
+            "bench_iterations",
+            "base_url",
+            "session_file",
+            "submit_interval",
+        ]
+        .contains(&key)
+}
+
+fn is_day_param(day: usize, name: &str) -> bool {
+    DAY_KEYS
+        .iter()
+        .filter(|(day_keys_day, _)| *day_keys_day == day)
+        .flat_map(|(_, names)| names.iter())
+        .any(|known_name| match known_name.strip_suffix('*') {
+            Some(prefix) => name.len() > prefix.len() && name.starts_with(prefix),
+            None => name == *known_name,
+        })
+}
+
+fn parse_number(key: &str, value: &str) -> Result<usize, String> {
+    value.parse::<usize>().map_err(|e| {
+        format!(
+            "Failed to read configuration value for '{key}'. Error: {:?}",
+            e
+        )
+    })
+}
+
+fn find_config_file() -> Option<PathBuf> {
+    let home = env::var_os("HOME").map(PathBuf::from);
+
+    [Some(PathBuf::from(".")), home]
+        .into_iter()
+        .flatten()
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+// Understands the small part of TOML the configuration needs: `key = value` pairs, optionally
+// quoted, `[section]` headers prefixing the keys below them and `#` comments. A plain key=value
+// file is read just the same.
+fn parse_config_file(contents: &str) -> Result<Vec<(String, String)>, String> {
+    let mut section: Option<&str> = None;
+    let mut pairs = Vec::new();
+
+    for (line_index, line) in puzzle_reader::lines(contents).enumerate() {
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = Some(name.trim());
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or(format!("line {}: expected key = value", line_index + 1))?;
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(value);
+        let key = match section {
+            Some(section) => format!("{section}.{}", key.trim()),
+            None => key.trim().to_string(),
+        };
+
+        pairs.push((key, value.to_string()));
+    }
+
+    Ok(pairs)
+}
+
+// A `#` inside a quoted value is part of the value, like in `session_file = "a#b"`
+fn strip_comment(line: &str) -> &str {
+    let mut in_quotes = false;
+    for (index, character) in line.char_indices() {
+        match character {
+            '"' => in_quotes = !in_quotes,
+            '#' if !in_quotes => return &line[..index],
+            _ => (),
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comments_end_only_outside_of_quotes() {
+        let contents =
+            "session_file = \"a#b\" # the session\n# year = 2022\n[day_3]\ngear_symbols = \"#*\"";
+
+        assert_eq!(
+            parse_config_file(contents),
+            Ok(vec![
+                ("session_file".to_string(), "a#b".to_string()),
+                ("day_3.gear_symbols".to_string(), "#*".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn day_keys_have_to_be_read_by_that_day() {
+        for key in [
+            "day_1.vocabulary",
+            "day_2.max_red",
+            "day_2.at_least",
+            "day_3.gear_symbols",
+        ] {
+            assert!(is_known_key(key), "{key}");
+        }
+        for key in [
+            "day_3.gear_symbol",
+            "day_3.vocabulary",
+            "day_2.max_",
+            "day_4.bag",
+            "day_x.bag",
+            "day_1.",
+        ] {
+            assert!(!is_known_key(key), "{key}");
+        }
+    }
+}
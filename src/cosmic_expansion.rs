@@ -1,18 +1,10 @@
+use crate::arithmetic::{self, OverflowError};
+use crate::puzzle_reader;
 use std::collections::HashMap;
 
-pub fn calculate_distances_between_galaxies(file: &str) -> usize {
+pub fn calculate_distances_between_galaxies(file: &str) -> Result<u128, OverflowError> {
     let galaxies_map = parse_galaxies_map(file);
 
-    for row in galaxies_map.iter() {
-        println!(
-            "{}",
-            row.iter()
-                .map(|c| c.to_string())
-                .collect::<Vec<String>>()
-                .join(" ")
-        );
-    }
-
     let mut galaxy_index = 1;
     let mut galaxies_hashmap: HashMap<usize, (usize, usize)> = HashMap::new();
 
@@ -25,7 +17,7 @@ pub fn calculate_distances_between_galaxies(file: &str) -> usize {
         }
     }
  
-    let mut distance_sum: usize = 0;
+    let mut distance_sum: u128 = 0;
     for galaxy_a in 1..galaxy_index {
         for galaxy_b in 1..galaxy_index {
             if galaxy_a == galaxy_b { continue; }
@@ -35,31 +27,57 @@ pub fn calculate_distances_between_galaxies(file: &str) -> usize {
             let row_distance = if galaxy_a_row > galaxy_b_row { galaxy_a_row - galaxy_b_row } else { galaxy_b_row - galaxy_a_row };
             let column_distance = if galaxy_a_column > galaxy_b_column { galaxy_a_column - galaxy_b_column } else { galaxy_b_column - galaxy_a_column };
 
-            let distance = row_distance + column_distance;
-            distance_sum += distance
+            let distance = (row_distance + column_distance) as u128;
+            distance_sum = arithmetic::add(distance_sum, distance, "sum of galaxy distances")?;
         }
     }
     
-    distance_sum / 2
+    Ok(distance_sum / 2)
 }
 
-fn parse_galaxies_map(file: &str) -> Vec<Vec<char>> {
-    let matrix = file
-        .split("\n")
+pub struct Observation {
+    pub galaxies: Vec<(usize, usize)>,
+    pub empty_rows: Vec<usize>,
+    pub empty_columns: Vec<usize>,
+}
+
+// Galaxies and the rows and columns that expand, all in the coordinates of the unexpanded image
+pub fn observe_galaxies(file: &str) -> Observation {
+    let image = puzzle_reader::lines(file)
         .map(|line| line.chars().collect::<Vec<char>>())
         .filter(|row| !row.is_empty())
         .collect::<Vec<Vec<char>>>();
+    let width = image.first().map(|row| row.len()).unwrap_or(0);
 
-    println!("Raw map:\n");
-    for row in matrix.iter() {
-        println!(
-            "{}",
+    let galaxies = image
+        .iter()
+        .enumerate()
+        .flat_map(|(row_index, row)| {
             row.iter()
-                .map(|c| c.to_string())
-                .collect::<Vec<String>>()
-                .join(" ")
-        );
+                .enumerate()
+                .filter(|(_, element)| **element == '#')
+                .map(move |(col, _)| (row_index, col))
+        })
+        .collect::<Vec<(usize, usize)>>();
+    let empty_rows = (0..image.len())
+        .filter(|row_index| galaxies.iter().all(|galaxy| galaxy.0 != *row_index))
+        .collect::<Vec<usize>>();
+    let empty_columns = (0..width)
+        .filter(|col| galaxies.iter().all(|galaxy| galaxy.1 != *col))
+        .collect::<Vec<usize>>();
+
+    Observation {
+        galaxies,
+        empty_rows,
+        empty_columns,
     }
+}
+
+fn parse_galaxies_map(file: &str) -> Vec<Vec<char>> {
+    let matrix = puzzle_reader::lines(file)
+        .map(|line| line.chars().collect::<Vec<char>>())
+        .filter(|row| !row.is_empty())
+        .collect::<Vec<Vec<char>>>();
 
     let mut matrix_expanded_rows: Vec<Vec<char>> = Vec::new();
 
@@ -70,17 +88,6 @@ fn parse_galaxies_map(file: &str) -> Vec<Vec<char>> {
         }
     }
 
-    println!("Expanded rows map:\n");
-    for row in matrix_expanded_rows.iter() {
-        println!(
-            "{}",
-            row.iter()
-                .map(|c| c.to_string())
-                .collect::<Vec<String>>()
-                .join(" ")
-        );
-    }
-
     let mut matrix_expanded_columns: Vec<Vec<char>> = matrix_expanded_rows.clone();
 
     let matrix_width = matrix_expanded_rows
@@ -89,16 +96,9 @@ fn parse_galaxies_map(file: &str) -> Vec<Vec<char>> {
         .expect("Matrix must not be empty");
     let matrix_height = matrix_expanded_rows.len();
 
-    println!(
-        "\nDimensions: width: {}, height: {}",
-        matrix_width, matrix_height
-    );
-
     let mut added_columns: usize = 0;
     for col in 0..matrix_width {
-        println!("Col: {col}\n");
         let column_contains_galaxies = (0..matrix_height).into_iter().any(|row_index| {
-            println!("  Row: {row_index}");
             let matrix_element = matrix_expanded_rows
                 .get(row_index)
                 .and_then(|row| row.get(col))
@@ -108,23 +108,17 @@ fn parse_galaxies_map(file: &str) -> Vec<Vec<char>> {
         });
 
         if !column_contains_galaxies {
-            println!("Empty column!");
             let insert_index = col + added_columns;
-            println!("Added columns: {added_columns}, insert index: {insert_index}");
             for row_index in 0..matrix_height {
-                println!("    Inserting in row: {row_index}");
                 let row = matrix_expanded_columns
                     .get_mut(row_index)
                     .expect("Row must exist in matrix");
-                println!("Row to be updated: {:?}", row);
 
                 row.insert(insert_index, '.');
             }
 
             added_columns += 1;
         }
-
-        println!("\n");
     }
 
     matrix_expanded_columns
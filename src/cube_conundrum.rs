@@ -1,29 +1,133 @@
-use std::collections::HashMap;
+use crate::arithmetic::{self, OverflowError};
+use crate::explain::{Column, Explanation};
+use crate::puzzle_reader;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::io::BufRead;
 
-#[derive(Debug, PartialEq, Eq, Hash)]
-pub enum CubeColor {
-    Red,
-    Green,
-    Blue,
-}
+const DEFAULT_BAG: &str = "red=12, green=13, blue=14";
+
+// Any lowercase name, the puzzle only uses red, green and blue
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CubeColor(String);
 
 impl CubeColor {
     pub fn parse(s: &str) -> Option<CubeColor> {
-        match s {
-            "red" => Some(CubeColor::Red),
-            "green" => Some(CubeColor::Green),
-            "blue" => Some(CubeColor::Blue),
-            _ => None,
+        match !s.is_empty() && s.chars().all(|c| c.is_alphabetic() || c == '-' || c == '_') {
+            true => Some(CubeColor(s.to_lowercase())),
+            false => None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+// The most cubes of each color the bag can hold. Colors the bag does not list can have up to
+// `unknown_color_limit` cubes, none by default, so a game showing one is impossible.
+pub struct BagConstraints {
+    limits: Vec<(CubeColor, usize)>,
+    unknown_color_limit: usize,
+}
+
+impl BagConstraints {
+    // `spec` is `color=count` pairs separated by commas or new lines, `#` starts a comment.
+    // `file` replaces the defaults when given, `spec` replaces both and `overrides` change single
+    // colors on top.
+    pub fn build(
+        file: Option<&str>,
+        spec: Option<&str>,
+        overrides: &[(&str, &str)],
+        unknown_color_limit: usize,
+    ) -> Result<BagConstraints, String> {
+        let file_spec = file
+            .map(|path| {
+                fs::read_to_string(path)
+                    .map_err(|e| format!("Failed to read {path}. Error was: {:?}", e))
+            })
+            .transpose()?;
+
+        let mut constraints = BagConstraints {
+            limits: parse_bag_spec(spec.or(file_spec.as_deref()).unwrap_or(DEFAULT_BAG))?,
+            unknown_color_limit,
+        };
+        for (color, count) in overrides {
+            constraints.set_limit(parse_bag_entry(color, count)?);
+        }
+
+        Ok(constraints)
+    }
+
+    fn set_limit(&mut self, (color, limit): (CubeColor, usize)) {
+        match self.limits.iter_mut().find(|(known, _)| *known == color) {
+            Some(entry) => entry.1 = limit,
+            None => self.limits.push((color, limit)),
+        }
+    }
+
+    pub fn limit(&self, color: &CubeColor) -> usize {
+        self.limits
+            .iter()
+            .find(|(known, _)| known == color)
+            .map(|(_, limit)| *limit)
+            .unwrap_or(self.unknown_color_limit)
+    }
+
+    fn is_known(&self, color: &CubeColor) -> bool {
+        self.limits.iter().any(|(known, _)| known == color)
+    }
+}
+
+fn parse_bag_spec(spec: &str) -> Result<Vec<(CubeColor, usize)>, String> {
+    let mut limits: Vec<(CubeColor, usize)> = Vec::new();
+
+    for entry in puzzle_reader::lines(spec)
+        .flat_map(|line| line.split('#').next().unwrap_or("").split(','))
+        .map(|entry| entry.trim())
+        .filter(|entry| !entry.is_empty())
+    {
+        let (color, count) = entry
+            .split_once('=')
+            .ok_or(format!("Expected color=count in the bag, got '{entry}'"))?;
+        let (color, count) = parse_bag_entry(color, count)?;
+
+        if limits.iter().any(|(known, _)| *known == color) {
+            return Err(format!("The bag lists {} more than once", color.name()));
         }
+        limits.push((color, count));
     }
+
+    Ok(limits)
+}
+
+fn parse_bag_entry(color: &str, count: &str) -> Result<(CubeColor, usize), String> {
+    let color = CubeColor::parse(color.trim())
+        .ok_or(format!("'{}' is not a valid cube color", color.trim()))?;
+    let count = count
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| format!("'{}' is not a valid number of {} cubes", count.trim(), color.name()))?;
+    Ok((color, count))
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct CubeSet {
     pub cubes: HashMap<CubeColor, usize>,
 }
 
 impl CubeSet {
+    pub fn new() -> CubeSet {
+        CubeSet::default()
+    }
+
+    // A color given twice keeps the last count, as when parsing
+    pub fn with(mut self, color: CubeColor, count: usize) -> CubeSet {
+        self.cubes.insert(color, count);
+        self
+    }
+
     pub fn from_str(s: &str) -> CubeSet {
         let cubes = HashMap::from_iter(s.split(", ").filter_map(CubeSet::parse_entry));
         CubeSet { cubes }
@@ -43,13 +147,51 @@ impl CubeSet {
     }
 }
 
-#[derive(Debug)]
+// Printed the way the puzzle writes it, with the colors of a draw sorted by name so that parsing
+// the text gives the same set back
+impl fmt::Display for CubeSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut cubes = self.cubes.iter().collect::<Vec<(&CubeColor, &usize)>>();
+        cubes.sort();
+        let entries = cubes
+            .iter()
+            .map(|(color, count)| format!("{count} {}", color.name()))
+            .collect::<Vec<String>>();
+        write!(f, "{}", entries.join(", "))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CubesGame {
     game_id: usize,
     cube_sets: Vec<CubeSet>,
 }
 
+impl fmt::Display for CubesGame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cube_sets = self
+            .cube_sets
+            .iter()
+            .map(|cube_set| cube_set.to_string())
+            .collect::<Vec<String>>();
+        write!(f, "Game {}: {}", self.game_id, cube_sets.join("; "))
+    }
+}
+
 impl CubesGame {
+    // A game always has a draw, one without any would print as a game with a single empty draw
+    pub fn new(game_id: usize, first_draw: CubeSet) -> CubesGame {
+        CubesGame {
+            game_id,
+            cube_sets: vec![first_draw],
+        }
+    }
+
+    pub fn with_draw(mut self, cube_set: CubeSet) -> CubesGame {
+        self.cube_sets.push(cube_set);
+        self
+    }
+
     pub fn parse(s: &str) -> Option<CubesGame> {
         let mut split_game = s.split(": ");
         let maybe_game_id = split_game.next().and_then(|s| {
@@ -67,50 +209,422 @@ impl CubesGame {
             _ => None,
         })
     }
+
+    // The most cubes of each color shown in any draw, sorted by color
+    pub fn minimal_bag(&self) -> Vec<(CubeColor, usize)> {
+        let mut minimal_bag: BTreeMap<CubeColor, usize> = BTreeMap::new();
+        for (color, &count) in self.cube_sets.iter().flat_map(|cube_set| cube_set.cubes.iter()) {
+            let needed = minimal_bag.entry(color.clone()).or_insert(0);
+            *needed = (*needed).max(count);
+        }
+        minimal_bag.into_iter().collect()
+    }
+}
+
+pub fn find_possible_games(games_log: &str, bag: &BagConstraints) -> Result<u128, OverflowError> {
+    let games: Vec<CubesGame> = puzzle_reader::lines(games_log).filter_map(CubesGame::parse).collect();
+
+    let valid_games = games
+        .iter()
+        .filter(|game| is_game_possible(game, bag));
+
+    arithmetic::sum(valid_games.map(|game| game.game_id as u128), "sum of possible game ids")
+}
+
+pub fn power_of_minimal_possible_games(games_log: &str) -> Result<u128, OverflowError> {
+    let games: Vec<CubesGame> = puzzle_reader::lines(games_log).filter_map(CubesGame::parse).collect();
+
+    let powers = games
+        .iter()
+        .map(game_power)
+        .collect::<Result<Vec<u128>, OverflowError>>()?;
+
+    arithmetic::sum(powers, "sum of game powers")
+}
+
+pub fn find_possible_games_from_reader<R: BufRead>(games_log: R, bag: &BagConstraints) -> Result<u128, String> {
+    puzzle_reader::stream_lines(games_log).try_fold(0, |acc, line| match CubesGame::parse(&line?) {
+        Some(game) if is_game_possible(&game, bag) => {
+            arithmetic::add(acc, game.game_id as u128, "sum of possible game ids").map_err(|e| e.to_string())
+        }
+        _ => Ok(acc),
+    })
+}
+
+pub fn power_of_minimal_possible_games_from_reader<R: BufRead>(games_log: R) -> Result<u128, String> {
+    puzzle_reader::stream_lines(games_log).try_fold(0, |acc, line| match CubesGame::parse(&line?) {
+        Some(game) => game_power(&game)
+            .and_then(|power| arithmetic::add(acc, power, "sum of game powers"))
+            .map_err(|e| e.to_string()),
+        None => Ok(acc),
+    })
+}
+
+fn is_game_possible(game: &CubesGame, bag: &BagConstraints) -> bool {
+    game.cube_sets.iter().all(|cube_set| {
+        cube_set
+            .cubes
+            .iter()
+            .all(|(color, num_of_cubes)| *num_of_cubes <= bag.limit(color))
+    })
+}
+
+// The colors a game does not show would count as 1 and not change the product
+fn game_power(game: &CubesGame) -> Result<u128, OverflowError> {
+    arithmetic::product(
+        game.minimal_bag().into_iter().map(|(_, n)| n as u128),
+        "power of a game",
+    )
+}
+
+// Why a game is possible or not: the fewest cubes of each color it could be played with, sorted by
+// color, and the first draw asking for more than the bag holds
+pub struct GameFeasibility {
+    pub game_id: usize,
+    pub minimal_bag: Vec<(CubeColor, usize)>,
+    pub first_violation: Option<Violation>,
+}
+
+// Draws count from 1. When a draw goes over for several colors the largest excess is kept.
+pub struct Violation {
+    pub draw: usize,
+    pub color: CubeColor,
+    pub count: usize,
+    pub limit: usize,
+    pub excess: usize,
+}
+
+impl GameFeasibility {
+    pub fn needs(&self, color: &CubeColor) -> usize {
+        self.minimal_bag
+            .iter()
+            .find(|(known, _)| known == color)
+            .map(|(_, n)| *n)
+            .unwrap_or(0)
+    }
 }
 
-pub fn find_possible_games(
-    games_log: &str,
-    max_red: usize,
-    max_green: usize,
-    max_blue: usize,
-) -> usize {
-    let games: Vec<CubesGame> = games_log.split("\n").filter_map(CubesGame::parse).collect();
-
-    let valid_games = games.iter().filter(|game| {
-        game.cube_sets.iter().all(|cube_set| {
-            cube_set
-                .cubes
-                .iter()
-                .all(|(color, num_of_cubes)| match color {
-                    CubeColor::Red => num_of_cubes <= &max_red,
-                    CubeColor::Green => num_of_cubes <= &max_green,
-                    CubeColor::Blue => num_of_cubes <= &max_blue,
+pub fn analyze_game(game: &CubesGame, bag: &BagConstraints) -> GameFeasibility {
+    let first_violation = game.cube_sets.iter().enumerate().find_map(|(index, cube_set)| {
+        cube_set
+            .cubes
+            .iter()
+            .filter_map(|(color, &count)| {
+                let limit = bag.limit(color);
+                (count > limit).then(|| Violation {
+                    draw: index + 1,
+                    color: color.clone(),
+                    count,
+                    limit,
+                    excess: count - limit,
                 })
-        })
+            })
+            .max_by(|a, b| a.excess.cmp(&b.excess).then(b.color.cmp(&a.color)))
     });
 
-    valid_games.fold(0, |acc, game| acc + game.game_id)
+    GameFeasibility {
+        game_id: game.game_id,
+        minimal_bag: game.minimal_bag(),
+        first_violation,
+    }
+}
+
+pub fn analyze_games(games_log: &str, bag: &BagConstraints) -> Vec<GameFeasibility> {
+    puzzle_reader::lines(games_log)
+        .filter_map(CubesGame::parse)
+        .map(|game| analyze_game(&game, bag))
+        .collect()
 }
 
-pub fn power_of_minimal_possible_games(games_log: &str) -> usize {
-    let games: Vec<CubesGame> = games_log.split("\n").filter_map(CubesGame::parse).collect();
+// The minimal bags of a whole log: for every color seen, how many games need exactly n cubes of
+// it, and the bags with the fewest cubes overall that keep at least k games possible
+pub struct BagStats {
+    pub colors: Vec<CubeColor>,
+    pub histogram: Vec<(usize, Vec<usize>)>,
+    pub tightest: Vec<TightestBag>,
+}
+
+pub struct TightestBag {
+    pub at_least: usize,
+    pub limits: Vec<usize>,
+    pub possible_games: usize,
+}
+
+pub fn bag_stats(games: &[GameFeasibility], at_least: &[usize]) -> BagStats {
+    let mut colors = games
+        .iter()
+        .flat_map(|game| game.minimal_bag.iter().map(|(color, _)| color.clone()))
+        .collect::<Vec<CubeColor>>();
+    colors.sort();
+    colors.dedup();
+
+    let needs = games
+        .iter()
+        .map(|game| colors.iter().map(|color| game.needs(color)).collect())
+        .collect::<Vec<Vec<usize>>>();
+
+    let mut histogram: Vec<(usize, Vec<usize>)> = Vec::new();
+    for game_needs in needs.iter() {
+        for (color_index, &n) in game_needs.iter().enumerate() {
+            let row = match histogram.iter().position(|(count, _)| *count == n) {
+                Some(row) => row,
+                None => {
+                    histogram.push((n, vec![0; colors.len()]));
+                    histogram.len() - 1
+                }
+            };
+            histogram[row].1[color_index] += 1;
+        }
+    }
+    histogram.sort();
 
-    games
+    let tightest = at_least
         .iter()
-        .map(|game| {
-            let max_red = get_max_num_of_cubes_in_game(game, CubeColor::Red).unwrap_or(1);
-            let max_green = get_max_num_of_cubes_in_game(game, CubeColor::Green).unwrap_or(1);
-            let max_blue = get_max_num_of_cubes_in_game(game, CubeColor::Blue).unwrap_or(1);
-            max_red * max_green * max_blue
+        .filter_map(|&k| tightest_bag(&needs, colors.len(), k))
+        .collect();
+
+    BagStats {
+        colors,
+        histogram,
+        tightest,
+    }
+}
+
+// The bag with the fewest cubes in total keeping at least `at_least` games possible, the first in
+// color order among equally small ones. Every limit worth trying is some game's minimal count, so
+// the search goes through those color by color and drops branches that already hold too many cubes
+// or keep too few games.
+fn tightest_bag(needs: &[Vec<usize>], color_count: usize, at_least: usize) -> Option<TightestBag> {
+    if at_least == 0 || at_least > needs.len() {
+        return None;
+    }
+
+    let candidates = (0..color_count)
+        .map(|color| {
+            let mut values = needs.iter().map(|game| game[color]).collect::<Vec<usize>>();
+            values.sort();
+            values.dedup();
+            values
         })
-        .fold(0, |acc, elem| acc + elem)
+        .collect::<Vec<Vec<usize>>>();
+
+    let mut best: Option<(usize, Vec<usize>)> = None;
+    search_tightest_bag(
+        needs,
+        &candidates,
+        at_least,
+        (0..needs.len()).collect(),
+        &mut Vec::new(),
+        &mut best,
+    );
+
+    best.map(|(_, limits)| TightestBag {
+        at_least,
+        possible_games: needs
+            .iter()
+            .filter(|game| game.iter().zip(limits.iter()).all(|(n, limit)| n <= limit))
+            .count(),
+        limits,
+    })
+}
+
+fn search_tightest_bag(
+    needs: &[Vec<usize>],
+    candidates: &[Vec<usize>],
+    at_least: usize,
+    games: Vec<usize>,
+    limits: &mut Vec<usize>,
+    best: &mut Option<(usize, Vec<usize>)>,
+) {
+    let color = limits.len();
+    let spent = limits.iter().sum::<usize>();
+
+    if color == candidates.len() {
+        if best.as_ref().is_none_or(|(total, _)| spent < *total) {
+            *best = Some((spent, limits.clone()));
+        }
+        return;
+    }
+
+    for &limit in candidates[color].iter() {
+        if best.as_ref().is_some_and(|(total, _)| spent + limit >= *total) {
+            break;
+        }
+        let kept = games
+            .iter()
+            .copied()
+            .filter(|&game| needs[game][color] <= limit)
+            .collect::<Vec<usize>>();
+        if kept.len() < at_least {
+            continue;
+        }
+
+        limits.push(limit);
+        search_tightest_bag(needs, candidates, at_least, kept, limits, best);
+        limits.pop();
+    }
 }
 
-fn get_max_num_of_cubes_in_game(game: &CubesGame, color: CubeColor) -> Option<usize> {
-    game.cube_sets
+// A verdict for part one and the power for part two, both come from the minimal bag of a game.
+// There is a column for every color in the bag followed by the ones only the games show.
+pub fn explain_games(games_log: &str, bag: &BagConstraints) -> Explanation {
+    let games = puzzle_reader::lines(games_log).filter_map(CubesGame::parse).collect::<Vec<CubesGame>>();
+    let analyses = games.iter().map(|game| analyze_game(game, bag)).collect::<Vec<GameFeasibility>>();
+    let mut unknown_colors = analyses
         .iter()
-        .flat_map(|cube_set| cube_set.cubes.get(&color))
-        .max()
-        .map(|&n| n)
+        .flat_map(|analysis| analysis.minimal_bag.iter().map(|(color, _)| color.clone()))
+        .filter(|color| !bag.is_known(color))
+        .collect::<Vec<CubeColor>>();
+    unknown_colors.sort();
+    unknown_colors.dedup();
+    let colors = bag
+        .limits
+        .iter()
+        .map(|(color, _)| color.clone())
+        .chain(unknown_colors)
+        .collect::<Vec<CubeColor>>();
+
+    let columns = [Column::number("game")]
+        .into_iter()
+        .chain(colors.iter().map(|color| Column::number(color.name())))
+        .chain([Column::text("verdict"), Column::number("power")])
+        .collect::<Vec<Column>>();
+    let mut explanation = Explanation::new(columns);
+
+    for (game, analysis) in games.iter().zip(analyses.iter()) {
+        let seen = colors.iter().map(|color| match analysis.needs(color) {
+            0 => "-".to_string(),
+            n => n.to_string(),
+        });
+
+        let verdict = match &analysis.first_violation {
+            None => "possible".to_string(),
+            Some(violation) if bag.is_known(&violation.color) => format!(
+                "impossible, draw {} has {} {} > {} by {}",
+                violation.draw,
+                violation.count,
+                violation.color.name(),
+                violation.limit,
+                violation.excess
+            ),
+            Some(violation) => format!(
+                "impossible, draw {} has {} {} not in the bag",
+                violation.draw,
+                violation.count,
+                violation.color.name()
+            ),
+        };
+
+        let power = game_power(game)
+            .map(|n| n.to_string())
+            .unwrap_or_else(|e| e.to_string());
+
+        explanation.add_row(
+            [analysis.game_id.to_string()]
+                .into_iter()
+                .chain(seen)
+                .chain([verdict, power])
+                .collect(),
+        );
+    }
+
+    explanation
+}
+
+// The stats of the minimal bags, one row per count of cubes giving how many games need exactly
+// that many of each color, then the configured bag and the tightest bags for at least k games.
+// Without `at_least` the tightest bags are shown for a quarter, half, three quarters and all games.
+pub fn bag_report(games_log: &str, bag: &BagConstraints, at_least: Option<usize>) -> Result<Explanation, String> {
+    let analyses = analyze_games(games_log, bag);
+    let game_count = analyses.len();
+
+    let at_least = match at_least {
+        Some(k) if k == 0 || k > game_count => {
+            return Err(format!("At least has to be between 1 and the {game_count} games in the log"))
+        }
+        Some(k) => vec![k],
+        None => {
+            let mut quarters = (1..=4).map(|q| (game_count * q).div_ceil(4).max(1)).collect::<Vec<usize>>();
+            quarters.dedup();
+            quarters.retain(|&k| k <= game_count);
+            quarters
+        }
+    };
+    let stats = bag_stats(&analyses, &at_least);
+
+    let columns = [Column::text("bag")]
+        .into_iter()
+        .chain(stats.colors.iter().map(|color| Column::number(color.name())))
+        .chain([Column::number("total"), Column::text("games")])
+        .collect::<Vec<Column>>();
+    let mut report = Explanation::new(columns);
+    let row = |label: String, values: &[usize], total: String, games: String| {
+        [label]
+            .into_iter()
+            .chain(values.iter().map(|n| n.to_string()))
+            .chain([total, games])
+            .collect::<Vec<String>>()
+    };
+
+    for (count, games_per_color) in stats.histogram.iter() {
+        report.add_row(row(format!("needs {count}"), games_per_color, String::new(), String::new()));
+    }
+
+    let configured = stats.colors.iter().map(|color| bag.limit(color)).collect::<Vec<usize>>();
+    let possible = analyses.iter().filter(|analysis| analysis.first_violation.is_none()).count();
+    report.add_row(row(
+        "configured".to_string(),
+        &configured,
+        configured.iter().sum::<usize>().to_string(),
+        format!("{possible} of {game_count}"),
+    ));
+
+    for tightest in stats.tightest.iter() {
+        report.add_row(row(
+            format!("tightest for {}", tightest.at_least),
+            &tightest.limits,
+            tightest.limits.iter().sum::<usize>().to_string(),
+            format!("{} of {game_count}", tightest.possible_games),
+        ));
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn color(name: &str) -> CubeColor {
+        CubeColor::parse(name).expect("Invalid color")
+    }
+
+    #[test]
+    fn built_games_survive_printing_and_parsing() {
+        let game = CubesGame::new(
+            7,
+            CubeSet::new()
+                .with(color("red"), 4)
+                .with(color("blue"), 3),
+        )
+        .with_draw(CubeSet::new().with(color("green"), 2))
+        .with_draw(
+            CubeSet::new()
+                .with(color("sky-blue"), 12)
+                .with(color("green"), 1)
+                .with(color("red"), 0),
+        );
+
+        let text = game.to_string();
+        assert_eq!(text, "Game 7: 3 blue, 4 red; 2 green; 1 green, 0 red, 12 sky-blue");
+        assert_eq!(CubesGame::parse(&text), Some(game));
+    }
+
+    #[test]
+    fn the_last_count_of_a_color_wins() {
+        let cube_set = CubeSet::new().with(color("red"), 1).with(color("red"), 5);
+
+        assert_eq!(cube_set.to_string(), "5 red");
+        assert_eq!(CubeSet::from_str(&cube_set.to_string()), cube_set);
+    }
 }
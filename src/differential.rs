@@ -0,0 +1,557 @@
+use crate::explain::{Column, Explanation};
+use crate::gear_ratios::{self, Schematic, SchematicNumber, Symbol};
+use crate::panics;
+use crate::puzzle_reader;
+use crate::random::Rng;
+use crate::scratchcards::{self, ScratchCard};
+use crate::{boat_races, garden, generator, hot_springs, trebuchet};
+use regex::Regex;
+use std::time::{Duration, Instant};
+
+const DAY_ARG_PREFIX: &str = "--day=";
+const RUNS_ARG_PREFIX: &str = "--runs=";
+const SEED_ARG_PREFIX: &str = "--seed=";
+const BENCH_ARG_PREFIX: &str = "--bench=";
+const DEFAULT_RUNS: usize = 200;
+const MAX_INPUT_SIZE: usize = 8;
+const MIN_BENCH_SIZE: usize = 100;
+const REFERENCE_BUDGET: Duration = Duration::from_secs(10);
+
+// Pairs an optimized solver with a slow but obviously correct reference. Both get the same
+// generated inputs and have to agree on the answer.
+struct DifferentialCase {
+    day: usize,
+    name: &'static str,
+    generate: fn(&mut Rng, usize) -> String,
+    optimized: fn(&str) -> String,
+    reference: fn(&str) -> String,
+}
+
+enum Outcome {
+    Agree,
+    Disagree {
+        optimized: String,
+        reference: String,
+    },
+    // The reference cannot handle the input either, so the input is not a valid one
+    Invalid,
+}
+
+fn all_cases() -> Vec<DifferentialCase> {
+    vec![
+        DifferentialCase {
+            day: 1,
+            name: "trebuchet digit automaton vs regex matching",
+            generate: generator::generate_calibration_document,
+            optimized: |input| {
+                let vocabulary = trebuchet::Vocabulary::build("english", 9, None, false)
+                    .expect("Invalid vocabulary");
+                trebuchet::explain_calibration(input, &vocabulary).to_table()
+            },
+            reference: explain_calibration_with_regexes,
+        },
+        DifferentialCase {
+            day: 3,
+            name: "gear ratios row index vs comparing all symbol and number pairs",
+            generate: generator::generate_engine_schematic,
+            optimized: |input| {
+                format!(
+                    "{} {}",
+                    gear_ratios::count_engine_parts(input),
+                    gear_ratios::GearRules::build("any", 2, "product")
+                        .and_then(|rules| gear_ratios::count_gear_ratio(input, &rules)
+                            .map_err(|e| e.to_string()))
+                        .unwrap_or_else(|e| panic!("{e}"))
+                )
+            },
+            reference: compare_all_symbol_and_number_pairs,
+        },
+        DifferentialCase {
+            day: 4,
+            name: "scratchcards running total vs adding copies card by card",
+            generate: generator::generate_scratchcards,
+            optimized: |input| {
+                scratchcards::process_scratchcards(input)
+                    .map(|total| total.to_string())
+                    .unwrap_or_else(|e| e.to_string())
+            },
+            reference: add_card_copies_one_by_one,
+        },
+        DifferentialCase {
+            day: 5,
+            name: "garden range mapping vs seed by seed",
+            generate: generator::generate_almanac,
+            optimized: |input| garden::read_almanac_by_seed_ranges(input).to_string(),
+            reference: |input| garden::read_almanac_by_enumerating_seed_ranges(input).to_string(),
+        },
+        DifferentialCase {
+            day: 6,
+            name: "boat races margin vs enumeration",
+            generate: generator::generate_race_sheet,
+            optimized: |input| {
+                boat_races::calculate_race_winning_margin(input)
+                    .map(|margin| margin.to_string())
+                    .unwrap_or_else(|e| e.to_string())
+            },
+            reference: enumerate_race_winning_margin,
+        },
+        DifferentialCase {
+            day: 6,
+            name: "boat races single race vs enumeration",
+            generate: generator::generate_race_sheet,
+            optimized: |input| boat_races::calculate_winning_possibilities(input).to_string(),
+            reference: enumerate_single_race_winning_possibilities,
+        },
+        DifferentialCase {
+            day: 12,
+            name: "hot springs memoization vs enumeration",
+            generate: generator::generate_spring_records,
+            optimized: |input| {
+                hot_springs::calculate_arrangements(input, false)
+                    .map(|arrangements| arrangements.to_string())
+                    .unwrap_or_else(|e| e.to_string())
+            },
+            reference: enumerate_spring_arrangements,
+        },
+    ]
+}
+
+pub fn run(program_args: &[String]) -> Result<String, String> {
+    let runs =
+        puzzle_reader::read_number_arg(program_args, RUNS_ARG_PREFIX)?.unwrap_or(DEFAULT_RUNS);
+    let seed = puzzle_reader::read_number_arg(program_args, SEED_ARG_PREFIX)?.unwrap_or(2023);
+    let day = puzzle_reader::read_number_arg(program_args, DAY_ARG_PREFIX)?;
+
+    let cases = all_cases()
+        .into_iter()
+        .filter(|case| day.map(|d| d == case.day).unwrap_or(true))
+        .collect::<Vec<DifferentialCase>>();
+
+    if cases.is_empty() {
+        return Err("There are no differential tests for the selected day".to_string());
+    }
+
+    if let Some(size) = puzzle_reader::read_number_arg(program_args, BENCH_ARG_PREFIX)? {
+        return benchmark(&cases, seed, size);
+    }
+
+    let mut report: Vec<String> = Vec::new();
+    let mut failures = 0;
+
+    for case in cases.iter() {
+        let mut rng = Rng::new(seed as u64);
+        let mut failing_input: Option<String> = None;
+
+        for run in 0..runs {
+            // Inputs grow with the runs, so the first failure tends to be a small one already
+            let size = 1 + run * MAX_INPUT_SIZE / runs.max(1);
+            let input = (case.generate)(&mut rng, size);
+
+            if let Outcome::Disagree { .. } = compare(case, &input) {
+                failing_input = Some(input);
+                break;
+            }
+        }
+
+        match failing_input {
+            None => report.push(format!("Day {} {}: {} runs OK", case.day, case.name, runs)),
+            Some(input) => {
+                failures += 1;
+                let minimal_input = shrink(case, input);
+                let details = match compare(case, &minimal_input) {
+                    Outcome::Disagree {
+                        optimized,
+                        reference,
+                    } => format!("optimized: {optimized}, reference: {reference}"),
+                    _ => "".to_string(),
+                };
+                report.push(format!(
+                    "Day {} {}: FAILED ({details})\nMinimal failing input:\n{minimal_input}",
+                    case.day, case.name
+                ));
+            }
+        }
+    }
+
+    let summary = report.join("\n");
+    if failures == 0 {
+        Ok(summary)
+    } else {
+        Err(format!("{summary}\n{failures} differential test(s) failed"))
+    }
+}
+
+// Times both solvers on inputs doubling in size up to the given one. References are slow by
+// design, so once one takes longer than the budget only the optimized solver gets the larger
+// inputs.
+fn benchmark(cases: &[DifferentialCase], seed: usize, size: usize) -> Result<String, String> {
+    let timed = |solver: fn(&str) -> String, input: &str| {
+        let started = Instant::now();
+        let answer = solver(input);
+        (answer, started.elapsed())
+    };
+    let mut sizes = (0..)
+        .map(|doublings| size >> doublings)
+        .take_while(|smaller_size| *smaller_size >= MIN_BENCH_SIZE)
+        .collect::<Vec<usize>>();
+    if sizes.is_empty() {
+        sizes.push(size);
+    }
+    sizes.reverse();
+
+    let mut report: Vec<String> = Vec::new();
+    let mut failures = 0;
+
+    for case in cases.iter() {
+        report.push(format!("Day {} {}:", case.day, case.name));
+        let mut reference_too_slow = false;
+
+        for size in sizes.iter() {
+            let input = (case.generate)(&mut Rng::new(seed as u64), *size);
+            let (optimized, optimized_time) = timed(case.optimized, &input);
+
+            if reference_too_slow {
+                report.push(format!(
+                    "  size {size}, {} bytes: optimized {optimized_time:?}, reference skipped",
+                    input.len()
+                ));
+                continue;
+            }
+
+            let (reference, reference_time) = timed(case.reference, &input);
+            let speedup = reference_time.as_secs_f64()
+                / optimized_time.max(Duration::from_nanos(1)).as_secs_f64();
+            report.push(format!(
+                "  size {size}, {} bytes: optimized {optimized_time:?}, reference {reference_time:?}, {speedup:.1}x faster",
+                input.len()
+            ));
+            if optimized != reference {
+                failures += 1;
+                report.push(format!(
+                    "  FAILED, optimized: {optimized}, reference: {reference}"
+                ));
+            }
+            reference_too_slow = reference_time > REFERENCE_BUDGET;
+        }
+    }
+
+    let summary = report.join("\n");
+    match failures {
+        0 => Ok(summary),
+        _ => Err(format!("{summary}\n{failures} benchmark(s) disagreed")),
+    }
+}
+
+// The solvers signal broken input by panicking, those panics are caught without flooding the report
+fn compare(case: &DifferentialCase, input: &str) -> Outcome {
+    let reference = match panics::catch(|| (case.reference)(input)) {
+        Ok(answer) => answer,
+        Err(_) => return Outcome::Invalid,
+    };
+    let optimized = panics::catch(|| (case.optimized)(input))
+        .unwrap_or_else(|caught| format!("panicked: {}", caught.message));
+
+    if optimized == reference {
+        Outcome::Agree
+    } else {
+        Outcome::Disagree {
+            optimized,
+            reference,
+        }
+    }
+}
+
+// Greedily drops whole lines and then single tokens for as long as the solvers keep disagreeing
+fn shrink(case: &DifferentialCase, input: String) -> String {
+    let mut smallest = input;
+
+    loop {
+        let smaller = shrink_candidates(&smallest)
+            .into_iter()
+            .find(|candidate| matches!(compare(case, candidate), Outcome::Disagree { .. }));
+
+        match smaller {
+            Some(candidate) => smallest = candidate,
+            None => break smallest,
+        }
+    }
+}
+
+fn shrink_candidates(input: &str) -> Vec<String> {
+    let lines = puzzle_reader::lines(input).collect::<Vec<&str>>();
+    let mut candidates: Vec<String> = Vec::new();
+
+    for skipped_line in 0..lines.len() {
+        let candidate = lines
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != skipped_line)
+            .map(|(_, line)| *line)
+            .collect::<Vec<&str>>();
+        candidates.push(candidate.join("\n"));
+    }
+
+    for (line_index, line) in lines.iter().enumerate() {
+        let tokens = line.split(' ').collect::<Vec<&str>>();
+        // Tokens like `seeds:` or `map:` only label the data, the input is broken without them
+        for skipped_token in (0..tokens.len()).filter(|i| !tokens[*i].ends_with(':')) {
+            let shortened_line = tokens
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != skipped_token)
+                .map(|(_, token)| *token)
+                .collect::<Vec<&str>>()
+                .join(" ");
+            let mut candidate = lines.clone();
+            candidate[line_index] = &shortened_line;
+            candidates.push(candidate.join("\n"));
+        }
+    }
+
+    // Dropping an empty token leaves the input as it was, which would keep the shrinking going
+    candidates.retain(|candidate| candidate.len() < input.len());
+    candidates
+}
+
+// Every symbol against every number, the O(symbols × numbers) adjacency the row index replaced
+fn compare_all_symbol_and_number_pairs(input: &str) -> String {
+    let schematic = Schematic::parse(input);
+    let is_adjacent = |number: &SchematicNumber, symbol: &Symbol| {
+        symbol.row.abs_diff(number.row) <= 1
+            && symbol.column + 1 >= number.column
+            && symbol.column <= number.column + number.length
+    };
+
+    let part_numbers_sum = schematic
+        .numbers()
+        .iter()
+        .filter(|number| {
+            schematic
+                .symbols()
+                .iter()
+                .any(|symbol| is_adjacent(number, symbol))
+        })
+        .map(|number| number.value)
+        .sum::<usize>();
+
+    let gear_ratios_sum = schematic
+        .symbols()
+        .iter()
+        .map(|symbol| {
+            let values = schematic
+                .numbers()
+                .iter()
+                .filter(|number| is_adjacent(number, symbol))
+                .map(|number| number.value)
+                .collect::<Vec<usize>>();
+            match values[..] {
+                [a, b] => a * b,
+                _ => 0,
+            }
+        })
+        .sum::<usize>();
+
+    format!("{part_numbers_sum} {gear_ratios_sum}")
+}
+
+fn add_card_copies_one_by_one(input: &str) -> String {
+    let cards = ScratchCard::parse_many(input);
+    let mut copies: Vec<u128> = vec![1; cards.len()];
+
+    for (index, card) in cards.iter().enumerate() {
+        for won in index + 1..=index + card.num_of_winning_numbers {
+            if let Some(won_copies) = copies.get(won).copied() {
+                copies[won] = won_copies + copies[index];
+            }
+        }
+    }
+
+    copies.iter().sum::<u128>().to_string()
+}
+
+fn count_ways_to_win(time: usize, best_distance: usize) -> usize {
+    (0..=time)
+        .filter(|charging_time| charging_time * (time - charging_time) > best_distance)
+        .count()
+}
+
+fn parse_race_sheet_line(line: &str) -> Vec<usize> {
+    line.split_whitespace()
+        .skip(1)
+        .map(|n| n.parse::<usize>().expect("Invalid race sheet"))
+        .collect()
+}
+
+fn enumerate_race_winning_margin(input: &str) -> String {
+    let mut lines = puzzle_reader::lines(input);
+    let times = lines.next().map(parse_race_sheet_line).unwrap_or_default();
+    let distances = lines.next().map(parse_race_sheet_line).unwrap_or_default();
+
+    if times.is_empty() || times.len() != distances.len() {
+        panic!("Invalid race sheet");
+    }
+
+    times
+        .iter()
+        .zip(distances.iter())
+        .map(|(time, distance)| count_ways_to_win(*time, *distance))
+        .product::<usize>()
+        .to_string()
+}
+
+fn enumerate_single_race_winning_possibilities(input: &str) -> String {
+    let mut lines = puzzle_reader::lines(input).map(|line| {
+        line.split_whitespace()
+            .skip(1)
+            .collect::<String>()
+            .parse::<usize>()
+            .expect("Invalid race sheet")
+    });
+
+    match (lines.next(), lines.next()) {
+        (Some(time), Some(distance)) => count_ways_to_win(time, distance).to_string(),
+        _ => panic!("Invalid race sheet"),
+    }
+}
+
+// Every word gets its own regex and the matches of all of them are put in order
+fn explain_calibration_with_regexes(input: &str) -> String {
+    let words = [
+        "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+    ];
+    let regexes = words
+        .iter()
+        .map(|word| Regex::new(word).expect("Invalid pattern"))
+        .chain([Regex::new("[0-9]").expect("Invalid pattern")])
+        .collect::<Vec<Regex>>();
+
+    let mut explanation = Explanation::new(vec![
+        Column::number("line"),
+        Column::text("text"),
+        Column::text("tokens"),
+        Column::number("first"),
+        Column::number("last"),
+        Column::number("value"),
+    ]);
+    for (index, line) in puzzle_reader::lines(input).enumerate() {
+        let mut matches = regexes
+            .iter()
+            .flat_map(|regex| regex.find_iter(line))
+            .map(|found| {
+                let digit = match words.iter().position(|word| *word == found.as_str()) {
+                    Some(position) => position + 1,
+                    None => found.as_str().parse::<usize>().expect("Not a digit"),
+                };
+                (found.start(), found.as_str(), digit)
+            })
+            .collect::<Vec<(usize, &str, usize)>>();
+        matches.sort_by_key(|(start, _, _)| *start);
+
+        let (first, last) = match (matches.first(), matches.last()) {
+            (Some((_, _, first)), Some((_, _, last))) => (first.to_string(), last.to_string()),
+            _ => (String::new(), String::new()),
+        };
+        explanation.add_row(vec![
+            (index + 1).to_string(),
+            line.to_string(),
+            matches
+                .iter()
+                .map(|(start, text, _)| format!("{text}@{}", line[..*start].chars().count() + 1))
+                .collect::<Vec<String>>()
+                .join(" "),
+            first.clone(),
+            last.clone(),
+            format!("{first}{last}")
+                .parse::<usize>()
+                .unwrap_or(0)
+                .to_string(),
+        ]);
+    }
+
+    explanation.to_table()
+}
+
+fn enumerate_spring_arrangements(input: &str) -> String {
+    puzzle_reader::lines(input)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let (springs, groups) = hot_springs::parse(line, false);
+            let unknown_positions = springs
+                .char_indices()
+                .filter(|(_, c)| *c == '?')
+                .map(|(i, _)| i)
+                .collect::<Vec<usize>>();
+
+            (0..1_usize << unknown_positions.len())
+                .filter(|assignment| {
+                    let mut candidate = springs.chars().collect::<Vec<char>>();
+                    for (bit, position) in unknown_positions.iter().enumerate() {
+                        candidate[*position] = if assignment & (1 << bit) != 0 {
+                            '#'
+                        } else {
+                            '.'
+                        };
+                    }
+                    let candidate_groups = candidate
+                        .split(|c| *c == '.')
+                        .filter(|group| !group.is_empty())
+                        .map(|group| group.len())
+                        .collect::<Vec<usize>>();
+                    candidate_groups == groups
+                })
+                .count()
+        })
+        .sum::<usize>()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn case(day: usize, name: &str) -> DifferentialCase {
+        all_cases()
+            .into_iter()
+            .find(|case| case.day == day && case.name == name)
+            .expect("No such differential case")
+    }
+
+    fn assert_agree(case: &DifferentialCase, input: &str) {
+        if let Outcome::Disagree {
+            optimized,
+            reference,
+        } = compare(case, input)
+        {
+            panic!(
+                "Day {} {} on {input:?}: optimized {optimized}, reference {reference}",
+                case.day, case.name
+            );
+        }
+    }
+
+    #[test]
+    fn optimized_solvers_agree_with_their_references() {
+        if let Err(report) = run(&[]) {
+            panic!("{report}");
+        }
+    }
+
+    #[test]
+    fn empty_leftovers_do_not_shadow_mapped_seeds() {
+        let input = "seeds: 2 2\n\nseed-to-soil map:\n\nsoil-to-fertilizer map:\n\nfertilizer-to-water map:\n\nwater-to-light map:\n\nlight-to-temperature map:\n\ntemperature-to-humidity map:\n6 2 2\n\nhumidity-to-location map:\n";
+        assert_agree(&case(5, "garden range mapping vs seed by seed"), input);
+    }
+
+    #[test]
+    fn holding_until_the_last_moment_can_win() {
+        let input = "Time: 3\nDistance: 0";
+        assert_agree(&case(6, "boat races single race vs enumeration"), input);
+        assert_eq!(boat_races::calculate_winning_possibilities(input), 2);
+    }
+
+    #[test]
+    fn a_race_that_can_not_be_won_leaves_no_margin() {
+        let input = "Time: 10 4\nDistance: 26 1";
+        assert_agree(&case(6, "boat races margin vs enumeration"), input);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::json;
+
+// The intermediate results a solver went through, one row per step, in the order they add up to
+// the answer
+pub struct Explanation {
+    columns: Vec<Column>,
+    rows: Vec<Vec<String>>,
+}
+
+// What the values of a column are, so that JSON gets the same type for it on every row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    Number,
+    Text,
+}
+
+pub struct Column {
+    name: String,
+    kind: ColumnKind,
+}
+
+impl Column {
+    pub fn number<S: ToString>(name: S) -> Column {
+        Column {
+            name: name.to_string(),
+            kind: ColumnKind::Number,
+        }
+    }
+
+    pub fn text<S: ToString>(name: S) -> Column {
+        Column {
+            name: name.to_string(),
+            kind: ColumnKind::Text,
+        }
+    }
+}
+
+impl Explanation {
+    pub fn new(columns: Vec<Column>) -> Explanation {
+        Explanation {
+            columns,
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn add_row(&mut self, values: Vec<String>) {
+        self.rows.push(values);
+    }
+
+    pub fn to_table(&self) -> String {
+        let widths = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                self.rows
+                    .iter()
+                    .filter_map(|row| row.get(i))
+                    .map(|value| value.chars().count())
+                    .chain([column.name.chars().count()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect::<Vec<usize>>();
+
+        let format_row = |values: &[String]| {
+            values
+                .iter()
+                .zip(widths.iter())
+                .map(|(value, &width)| format!("{value:width$}"))
+                .collect::<Vec<String>>()
+                .join(" | ")
+                .trim_end()
+                .to_string()
+        };
+        let separator = widths
+            .iter()
+            .map(|&width| "-".repeat(width))
+            .collect::<Vec<String>>()
+            .join("-+-");
+
+        let names = self
+            .columns
+            .iter()
+            .map(|column| column.name.clone())
+            .collect::<Vec<String>>();
+
+        [format_row(&names), separator]
+            .into_iter()
+            .chain(self.rows.iter().map(|row| format_row(row)))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    // An array with an object per row. A number column holds null where a row has no number in it,
+    // like a blank or `-`, and a text column is a string even when its value looks like a number.
+    pub fn to_json(&self) -> String {
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                let fields = self
+                    .columns
+                    .iter()
+                    .zip(row.iter())
+                    .map(|(column, value)| {
+                        let encoded = match column.kind {
+                            ColumnKind::Number => value
+                                .parse::<i128>()
+                                .map(|number| number.to_string())
+                                .unwrap_or("null".to_string()),
+                            ColumnKind::Text => json::string(value),
+                        };
+                        (column.name.as_str(), encoded)
+                    })
+                    .collect::<Vec<(&str, String)>>();
+                json::object(&fields)
+            })
+            .collect::<Vec<String>>();
+
+        format!("[{}]", rows.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_follows_the_declared_column_types() {
+        let mut explanation = Explanation::new(vec![
+            Column::number("rank"),
+            Column::text("hand"),
+            Column::number("bid"),
+        ]);
+        explanation.add_row(vec![
+            "1".to_string(),
+            "24937".to_string(),
+            "765".to_string(),
+        ]);
+        explanation.add_row(vec!["2".to_string(), "23T9K".to_string(), "-".to_string()]);
+
+        assert_eq!(
+            explanation.to_json(),
+            r#"[{"rank":1,"hand":"24937","bid":765},{"rank":2,"hand":"23T9K","bid":null}]"#
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use crate::arithmetic::OverflowError;
+use crate::polygon::Point;
+use crate::puzzle_reader::{self, PuzzleIdentifier};
+use crate::{clumsy_crucible, floor_will_be_lava, lavaduct_lagoon, pipe_maze};
+use std::fs;
+
+const PIXELS_PER_TILE: usize = 4;
+
+type Rgb = [u8; 3];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ImageFormat {
+    Svg,
+    Ppm,
+}
+
+impl ImageFormat {
+    pub fn from_path(path: &str) -> Result<ImageFormat, String> {
+        let extension = path.rsplit('.').next().unwrap_or("").to_lowercase();
+
+        match extension.as_str() {
+            "svg" => Ok(ImageFormat::Svg),
+            "ppm" => Ok(ImageFormat::Ppm),
+            _ => Err(format!(
+                "Unsupported export file '{path}', expected a .svg or a .ppm extension"
+            )),
+        }
+    }
+}
+
+// A binary PPM (P6) image, where every puzzle tile becomes a square of pixels
+struct Raster {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Raster {
+    pub fn from_tiles(tiles: &[Vec<Rgb>]) -> Raster {
+        let columns = tiles.iter().map(|row| row.len()).max().unwrap_or(0);
+        let width = columns * PIXELS_PER_TILE;
+        let height = tiles.len() * PIXELS_PER_TILE;
+        let mut pixels = vec![[0, 0, 0]; width * height];
+
+        for (row, tiles_row) in tiles.iter().enumerate() {
+            for (column, color) in tiles_row.iter().enumerate() {
+                for y in row * PIXELS_PER_TILE..(row + 1) * PIXELS_PER_TILE {
+                    for x in column * PIXELS_PER_TILE..(column + 1) * PIXELS_PER_TILE {
+                        pixels[y * width + x] = *color;
+                    }
+                }
+            }
+        }
+
+        Raster {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut bytes = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        bytes.extend(self.pixels.iter().flatten());
+        bytes
+    }
+}
+
+pub fn export_puzzle(identifier: &PuzzleIdentifier, input: &str, path: &str) -> Result<(), String> {
+    let format = ImageFormat::from_path(path)?;
+    let is_part_two = identifier.part == 2;
+
+    let contents = match (identifier.year, identifier.day, format) {
+        (2023, 10, ImageFormat::Ppm) => draw_pipe_loop(input).to_ppm(),
+        (2023, 16, ImageFormat::Ppm) => draw_energization_heatmap(input, is_part_two).to_ppm(),
+        (2023, 17, ImageFormat::Ppm) => draw_heat_loss_heatmap(input, is_part_two).to_ppm(),
+        (2023, 17, ImageFormat::Svg) => draw_crucible_route(input, is_part_two).into_bytes(),
+        (2023, 18, ImageFormat::Svg) => draw_lagoon(input, is_part_two)
+            .map_err(|e| e.to_string())?
+            .into_bytes(),
+        (year, day, format) => {
+            return Err(format!("{year} day {day} cannot be exported as {:?}", format));
+        }
+    };
+
+    fs::write(path, contents).map_err(|e| format!("Failed to write {path}. Error was: {:?}", e))
+}
+
+fn draw_pipe_loop(input: &str) -> Raster {
+    let pipe_maze::LoopTiles {
+        loop_tiles,
+        enclosed_tiles,
+    } = pipe_maze::find_loop_tiles(input);
+    let height = puzzle_reader::lines(input).count();
+    let width = puzzle_reader::lines(input)
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut tiles = vec![vec![[24, 24, 32]; width]; height];
+    for (row, column) in loop_tiles.iter() {
+        tiles[*row][*column] = [240, 200, 40];
+    }
+    for (row, column) in enclosed_tiles.iter() {
+        tiles[*row][*column] = [60, 200, 90];
+    }
+
+    Raster::from_tiles(&tiles)
+}
+
+// Part one shows the tiles energized from the top-left corner, part two counts for every tile how
+// many of the possible entries energize it
+fn draw_energization_heatmap(input: &str, all_entries: bool) -> Raster {
+    let heatmap = if all_entries {
+        floor_will_be_lava::get_energization_heatmap(input)
+    } else {
+        floor_will_be_lava::get_energization_map(input)
+            .iter()
+            .map(|row| row.iter().map(|tile| *tile as usize).collect())
+            .collect()
+    };
+    let max_heat = heatmap.iter().flatten().max().copied().unwrap_or(0);
+
+    let tiles = heatmap
+        .iter()
+        .map(|row| row.iter().map(|heat| heat_color(*heat, max_heat)).collect())
+        .collect::<Vec<Vec<Rgb>>>();
+
+    Raster::from_tiles(&tiles)
+}
+
+fn draw_heat_loss_heatmap(input: &str, ultra_crucible: bool) -> Raster {
+    let heat_loss_map = clumsy_crucible::get_heat_loss_map(input);
+    let mut tiles = heat_loss_map
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|heat_loss| heat_color(*heat_loss as usize, 9))
+                .collect()
+        })
+        .collect::<Vec<Vec<Rgb>>>();
+
+    if let Some((_, route)) = clumsy_crucible::find_best_route(input, ultra_crucible) {
+        for (row, column) in route.iter() {
+            tiles[*row][*column] = [80, 220, 255];
+        }
+    }
+
+    Raster::from_tiles(&tiles)
+}
+
+fn draw_crucible_route(input: &str, ultra_crucible: bool) -> String {
+    let heat_loss_map = clumsy_crucible::get_heat_loss_map(input);
+    let height = heat_loss_map.len();
+    let width = heat_loss_map.first().map(|row| row.len()).unwrap_or(0);
+
+    let mut elements: Vec<String> = Vec::new();
+    for (row, heat_losses) in heat_loss_map.iter().enumerate() {
+        for (column, heat_loss) in heat_losses.iter().enumerate() {
+            elements.push(format!(
+                r#"<rect x="{column}" y="{row}" width="1" height="1" fill="{}"/>"#,
+                to_hex(heat_color(*heat_loss as usize, 9))
+            ));
+        }
+    }
+
+    if let Some((heat_loss, route)) = clumsy_crucible::find_best_route(input, ultra_crucible) {
+        let points = route
+            .iter()
+            .map(|(row, column)| format!("{}.5,{}.5", column, row))
+            .collect::<Vec<String>>()
+            .join(" ");
+        elements.push(format!(
+            r##"<polyline points="{points}" fill="none" stroke="#50dcff" stroke-width="0.3" stroke-linejoin="round"><title>Heat loss: {heat_loss}</title></polyline>"##
+        ));
+    }
+
+    svg_document((0, 0, width as i64, height as i64), &elements)
+}
+
+// Trench coordinates grow upwards while SVG ones grow downwards, hence the flipped y
+fn draw_lagoon(input: &str, decode_colors: bool) -> Result<String, OverflowError> {
+    let outline = lavaduct_lagoon::get_lagoon_outline(input, decode_colors)?
+        .iter()
+        .map(|(x, y)| (*x, -*y))
+        .collect::<Vec<Point>>();
+
+    let min_x = outline.iter().map(|p| p.0).min().unwrap_or(0);
+    let max_x = outline.iter().map(|p| p.0).max().unwrap_or(0);
+    let min_y = outline.iter().map(|p| p.1).min().unwrap_or(0);
+    let max_y = outline.iter().map(|p| p.1).max().unwrap_or(0);
+    let padding = ((max_x - min_x).max(max_y - min_y) / 50).max(1);
+
+    let path = outline
+        .iter()
+        .enumerate()
+        .map(|(i, (x, y))| format!("{}{x} {y}", if i == 0 { "M" } else { "L" }))
+        .collect::<Vec<String>>()
+        .join(" ");
+    let element = format!(
+        r##"<path d="{path} Z" fill="#7a4a2a" fill-opacity="0.6" stroke="#e04020" stroke-width="2" vector-effect="non-scaling-stroke"/>"##
+    );
+
+    Ok(svg_document(
+        (
+            min_x - padding,
+            min_y - padding,
+            max_x - min_x + 2 * padding,
+            max_y - min_y + 2 * padding,
+        ),
+        &[element],
+    ))
+}
+
+fn svg_document(view_box: (i64, i64, i64, i64), elements: &[String]) -> String {
+    let (x, y, width, height) = view_box;
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{x} {y} {width} {height}\" width=\"800\" height=\"{}\">\n{}\n</svg>\n",
+        800 * height / width.max(1),
+        elements.join("\n")
+    )
+}
+
+// Goes from dark blue through red to yellow as the value approaches the maximum
+fn heat_color(value: usize, max_value: usize) -> Rgb {
+    const STOPS: [Rgb; 4] = [[20, 20, 60], [140, 30, 120], [230, 60, 30], [250, 230, 80]];
+
+    if max_value == 0 {
+        return STOPS[0];
+    }
+
+    let position = value.min(max_value) as f64 / max_value as f64 * (STOPS.len() - 1) as f64;
+    let lower = (position.floor() as usize).min(STOPS.len() - 2);
+    let ratio = position - lower as f64;
+
+    let mut color = [0; 3];
+    for channel in 0..3 {
+        let from = STOPS[lower][channel] as f64;
+        let to = STOPS[lower + 1][channel] as f64;
+        color[channel] = (from + (to - from) * ratio).round() as u8;
+    }
+    color
+}
+
+fn to_hex(color: Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
@@ -1,34 +1,63 @@
+use crate::puzzle_reader;
 use std::collections::HashMap;
 
 pub fn calculate_energized_tiles(file: &str) -> usize {
+    let energization_map = get_energization_map(file);
+
+    energization_map.iter().fold(0, |acc, row| {
+        acc + row.iter().fold(0, |acc_2, tile| acc_2 + *tile as usize)
+    })
+}
+
+// Marks every tile energized by the beam entering the top-left corner heading right with 1
+pub fn get_energization_map(file: &str) -> Vec<Vec<u8>> {
     let lines = parse_input(file);
-    let width = lines.first().unwrap().len();
 
-    let mut energization_map = vec![vec![0 as u8; width]; lines.len()];
+    energize_from_entry(&lines, &((0, 0), BeamDirection::Right))
+}
 
-    let mut cache: HashMap<String, BeamDirection> = HashMap::new();
+pub fn find_best_beam_entry(file: &str) -> usize {
+    let lines = parse_input(file);
 
-    follow_beam(
-        &lines,
-        &mut energization_map,
-        (0, 0),
-        BeamDirection::Right,
-        &mut cache,
-    );
+    let mut max_energized_tiles = 0;
 
-    for row in energization_map.iter() {
-        println!("{:?}", row);
+    for entry in get_all_beam_entries(&lines).iter() {
+        let energization_map = energize_from_entry(&lines, entry);
+
+        let total_energized_tiles = energization_map.iter().fold(0, |acc, row| {
+            acc + row.iter().fold(0, |acc_2, tile| acc_2 + *tile as usize)
+        });
+        if total_energized_tiles > max_energized_tiles {
+            max_energized_tiles = total_energized_tiles;
+        }
     }
 
-    energization_map.iter().fold(0, |acc, row| {
-        acc + row.iter().fold(0, |acc_2, tile| acc_2 + *tile as usize)
-    })
+    max_energized_tiles
 }
 
-pub fn find_best_beam_entry(file: &str) -> usize {
+// For every tile, the number of beam entries along the edges which end up energizing it
+pub fn get_energization_heatmap(file: &str) -> Vec<Vec<usize>> {
     let lines = parse_input(file);
     let width = lines.first().unwrap().len();
 
+    let mut heatmap = vec![vec![0; width]; lines.len()];
+
+    for entry in get_all_beam_entries(&lines).iter() {
+        let energization_map = energize_from_entry(&lines, entry);
+
+        for (row, tiles) in energization_map.iter().enumerate() {
+            for (column, tile) in tiles.iter().enumerate() {
+                heatmap[row][column] += *tile as usize;
+            }
+        }
+    }
+
+    heatmap
+}
+
+fn get_all_beam_entries(lines: &[Vec<char>]) -> Vec<((usize, usize), BeamDirection)> {
+    let width = lines.first().unwrap().len();
+
     let mut entries_from_above = (0..width)
         .map(|i| ((0, i), BeamDirection::Down))
         .collect::<Vec<((usize, usize), BeamDirection)>>();
@@ -48,29 +77,26 @@ pub fn find_best_beam_entry(file: &str) -> usize {
     all_entries_to_verify.append(&mut entries_from_below);
     all_entries_to_verify.append(&mut entries_from_right);
 
-    let mut max_energized_tiles = 0;
-
-    for entry in all_entries_to_verify.iter() {
-        let (entry_tile, entry_direction) = entry;
-        let mut energization_map = vec![vec![0 as u8; width]; lines.len()];
-        let mut cache: HashMap<String, BeamDirection> = HashMap::new();
-        follow_beam(
-            &lines,
-            &mut energization_map,
-            *entry_tile,
-            *entry_direction,
-            &mut cache,
-        );
+    all_entries_to_verify
+}
 
-        let total_energized_tiles = energization_map.iter().fold(0, |acc, row| {
-            acc + row.iter().fold(0, |acc_2, tile| acc_2 + *tile as usize)
-        });
-        if total_energized_tiles > max_energized_tiles {
-            max_energized_tiles = total_energized_tiles;
-        }
-    }
+fn energize_from_entry(
+    lines: &Vec<Vec<char>>,
+    entry: &((usize, usize), BeamDirection),
+) -> Vec<Vec<u8>> {
+    let (entry_tile, entry_direction) = entry;
+    let width = lines.first().unwrap().len();
+    let mut energization_map = vec![vec![0_u8; width]; lines.len()];
+    let mut cache: HashMap<String, BeamDirection> = HashMap::new();
+    follow_beam(
+        lines,
+        &mut energization_map,
+        *entry_tile,
+        *entry_direction,
+        &mut cache,
+    );
 
-    max_energized_tiles
+    energization_map
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -88,7 +114,6 @@ fn follow_beam(
     beam_direction: BeamDirection,
     visited_tiles: &mut HashMap<String, BeamDirection>,
 ) -> () {
-    println!("\nStarting new beam");
     let mut current_tile_index = beam_start;
     let mut current_beam_direction = beam_direction;
     loop {
@@ -103,14 +128,10 @@ fn follow_beam(
         let current_tile = tiles_schema
             .get(current_tile_index.0)
             .and_then(|row| row.get(current_tile_index.1));
-        println!("Current tile: {:?}", current_tile);
-        println!("Current tile index: {:?}", current_tile_index);
-        println!("Current direction: {:?}", current_beam_direction);
 
         let next_tile_index = match current_tile {
             None => None,
             Some(c) => {
-                println!("Updating map: {:?}", current_tile_index);
                 energization_map[current_tile_index.0][current_tile_index.1] = 1;
 
                 match c {
@@ -217,14 +238,9 @@ fn follow_beam(
         };
 
         if next_tile_index.is_none() {
-            println!("Split the beam\n");
             break;
         } else {
-            println!("Continue the beam!");
             current_tile_index = next_tile_index.unwrap();
-            println!("updated index: {:?}", current_tile_index);
-            println!("updated direction: {:?}", current_beam_direction);
-            println!("");
         }
     }
 }
@@ -259,7 +275,7 @@ fn get_next_tile(
 }
 
 fn parse_input(file: &str) -> Vec<Vec<char>> {
-    file.split("\n")
+    puzzle_reader::lines(file)
         .filter(|line| !line.is_empty())
         .map(|line| line.chars().collect::<Vec<char>>())
         .collect()
@@ -0,0 +1,450 @@
+use crate::config::Config;
+use crate::panics::{self, CaughtPanic};
+use crate::puzzle_reader;
+use crate::random::Rng;
+use crate::{
+    aplenty, camel_cards, clumsy_crucible, cosmic_expansion, cube_conundrum, floor_will_be_lava,
+    garden, gear_ratios, haunted_wasteland, hot_springs, lavaduct_lagoon, lens_library,
+    mirage_maintenance, pipe_maze, point_of_incidence, scratchcards, trebuchet,
+};
+
+const TARGET_ARG_PREFIX: &str = "--target=";
+const RUNS_ARG_PREFIX: &str = "--runs=";
+const SEED_ARG_PREFIX: &str = "--seed=";
+const DEFAULT_RUNS: usize = 10_000;
+const MAX_MUTATIONS: usize = 4;
+
+// Bytes that mean something to at least one of the parsers
+const INTERESTING_BYTES: &[u8] = b"0123456789:;,=-<>{}()#|.?*ALRUDxmas \n";
+
+// Feeds a parser with mutated samples of its day's input. The corpus picks the parts of the input
+// file that the parser gets to see.
+struct FuzzTarget {
+    name: &'static str,
+    day: usize,
+    corpus: fn(&str) -> Vec<String>,
+    parse: fn(&str),
+}
+
+fn all_targets() -> Vec<FuzzTarget> {
+    vec![
+        FuzzTarget {
+            name: "trebuchet::calibrate_using_spelled_digits",
+            day: 1,
+            corpus: each_line,
+            parse: |input| {
+                let vocabulary = trebuchet::Vocabulary::build("english", 9, None, false)
+                    .expect("Invalid vocabulary");
+                let _ = trebuchet::calibrate_using_spelled_digits(input, &vocabulary);
+            },
+        },
+        FuzzTarget {
+            name: "cube_conundrum::CubesGame::parse",
+            day: 2,
+            corpus: each_line,
+            // Whatever parses has to print back to text parsing to the same game
+            parse: |input| {
+                if let Some(game) = cube_conundrum::CubesGame::parse(input) {
+                    let printed = game.to_string();
+                    assert_eq!(cube_conundrum::CubesGame::parse(&printed), Some(game), "{printed}");
+                }
+            },
+        },
+        FuzzTarget {
+            name: "gear_ratios::Schematic::parse",
+            day: 3,
+            corpus: whole_file,
+            parse: |input| {
+                let rules = gear_ratios::GearRules::build("any", 2, "product")
+                    .expect("Invalid gear rules");
+                let _ = gear_ratios::count_engine_parts(input);
+                let _ = gear_ratios::count_gear_ratio(input, &rules);
+            },
+        },
+        FuzzTarget {
+            name: "scratchcards::ScratchCard::parse",
+            day: 4,
+            corpus: each_line,
+            parse: |input| {
+                let _ = scratchcards::ScratchCard::parse(input);
+            },
+        },
+        FuzzTarget {
+            name: "garden::Almanac::parse",
+            day: 5,
+            corpus: whole_file,
+            parse: |input| {
+                let _ = garden::Almanac::parse(input);
+            },
+        },
+        FuzzTarget {
+            name: "camel_cards::Hand::parse",
+            day: 7,
+            corpus: first_word_of_each_line,
+            parse: |input| {
+                let _ = camel_cards::Hand::parse(input);
+            },
+        },
+        FuzzTarget {
+            name: "camel_cards::Hand::parse_with_jokers",
+            day: 7,
+            corpus: first_word_of_each_line,
+            parse: |input| {
+                let _ = camel_cards::Hand::parse_with_jokers(input);
+            },
+        },
+        FuzzTarget {
+            name: "haunted_wasteland::NodePair::parse",
+            day: 8,
+            corpus: |file| {
+                puzzle_reader::lines(file)
+                    .filter_map(|line| line.split(" = ").nth(1))
+                    .map(|pair| pair.to_string())
+                    .collect()
+            },
+            parse: |input| {
+                let _ = haunted_wasteland::NodePair::parse(input);
+            },
+        },
+        // Days 9, 11, 13 and 16 parse the input inside their solvers, so those are fuzzed as a whole
+        FuzzTarget {
+            name: "mirage_maintenance::oasis_report",
+            day: 9,
+            corpus: whole_file,
+            parse: |input| {
+                let _ = mirage_maintenance::oasis_report(input);
+                let _ = mirage_maintenance::oasis_report_backwards(input);
+            },
+        },
+        FuzzTarget {
+            name: "pipe_maze::Maze::parse",
+            day: 10,
+            corpus: whole_file,
+            parse: |input| {
+                let _ = pipe_maze::Maze::parse(input);
+            },
+        },
+        FuzzTarget {
+            name: "cosmic_expansion::calculate_distances_between_galaxies",
+            day: 11,
+            corpus: whole_file,
+            parse: |input| {
+                let _ = cosmic_expansion::calculate_distances_between_galaxies(input);
+            },
+        },
+        FuzzTarget {
+            name: "hot_springs::parse",
+            day: 12,
+            corpus: each_line,
+            parse: |input| {
+                let _ = hot_springs::parse(input, true);
+            },
+        },
+        FuzzTarget {
+            name: "point_of_incidence::count_reflections",
+            day: 13,
+            corpus: whole_file,
+            parse: |input| {
+                let _ = point_of_incidence::count_reflections(input);
+            },
+        },
+        FuzzTarget {
+            name: "lens_library::Instruction::parse",
+            day: 15,
+            corpus: |file| {
+                file.trim()
+                    .split(',')
+                    .map(|step| step.to_string())
+                    .collect()
+            },
+            parse: |input| {
+                let _ = lens_library::Instruction::parse(input);
+            },
+        },
+        FuzzTarget {
+            name: "floor_will_be_lava::get_energization_map",
+            day: 16,
+            corpus: whole_file,
+            parse: |input| {
+                let _ = floor_will_be_lava::get_energization_map(input);
+            },
+        },
+        FuzzTarget {
+            name: "clumsy_crucible::get_heat_loss_map",
+            day: 17,
+            corpus: whole_file,
+            parse: |input| {
+                let _ = clumsy_crucible::get_heat_loss_map(input);
+            },
+        },
+        FuzzTarget {
+            name: "lavaduct_lagoon::DigPlanEntry::parse_part_one",
+            day: 18,
+            corpus: each_line,
+            parse: |input| {
+                let _ = lavaduct_lagoon::DigPlanEntry::parse_part_one(input);
+            },
+        },
+        FuzzTarget {
+            name: "lavaduct_lagoon::DigPlanEntry::parse_part_two",
+            day: 18,
+            corpus: each_line,
+            parse: |input| {
+                let _ = lavaduct_lagoon::DigPlanEntry::parse_part_two(input);
+            },
+        },
+        FuzzTarget {
+            name: "aplenty::Workflow::parse",
+            day: 19,
+            corpus: |file| {
+                puzzle_reader::blocks(file)
+                    .next()
+                    .map(to_strings)
+                    .unwrap_or_default()
+            },
+            parse: |input| {
+                let _ = aplenty::Workflow::parse(input);
+            },
+        },
+        FuzzTarget {
+            name: "aplenty::Condition::parse",
+            day: 19,
+            corpus: |file| {
+                puzzle_reader::blocks(file)
+                    .next()
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(|workflow| workflow.split('{').nth(1))
+                    .flat_map(|rules| rules.split(','))
+                    .filter(|rule| rule.contains(':'))
+                    .map(|condition| condition.to_string())
+                    .collect()
+            },
+            parse: |input| {
+                let _ = aplenty::Condition::parse(input);
+            },
+        },
+        FuzzTarget {
+            name: "aplenty::Part::parse",
+            day: 19,
+            corpus: |file| {
+                puzzle_reader::blocks(file)
+                    .nth(1)
+                    .map(to_strings)
+                    .unwrap_or_default()
+            },
+            parse: |input| {
+                let _ = aplenty::Part::parse(input);
+            },
+        },
+    ]
+}
+
+pub fn run(program_args: &[String], config: &Config) -> Result<String, String> {
+    let runs =
+        puzzle_reader::read_number_arg(program_args, RUNS_ARG_PREFIX)?.unwrap_or(DEFAULT_RUNS);
+    let seed = puzzle_reader::read_number_arg(program_args, SEED_ARG_PREFIX)?.unwrap_or(2023);
+    let target_name = puzzle_reader::read_text_arg(program_args, TARGET_ARG_PREFIX);
+
+    let targets = all_targets()
+        .into_iter()
+        .filter(|target| target_name.map(|name| name == target.name).unwrap_or(true))
+        .collect::<Vec<FuzzTarget>>();
+
+    if targets.is_empty() {
+        let names = all_targets()
+            .iter()
+            .map(|target| target.name)
+            .collect::<Vec<&str>>();
+        return Err(format!(
+            "Unknown fuzz target, expected one of:\n{}",
+            names.join("\n")
+        ));
+    }
+
+    let mut report: Vec<String> = Vec::new();
+    let mut failures = 0;
+
+    for target in targets.iter() {
+        let mut rng = Rng::new(seed as u64);
+        // A missing input file only means starting from random bytes
+        let corpus = puzzle_reader::read_puzzle_input_file(
+            &config.input_dir,
+            puzzle_reader::DEFAULT_YEAR,
+            target.day,
+            1,
+        )
+        .map(|file| (target.corpus)(&file))
+        .unwrap_or_default();
+
+        let crash = corpus
+            .iter()
+            .cloned()
+            .chain((0..runs).map(|_| mutate(&mut rng, &corpus)))
+            .enumerate()
+            .find_map(|(run, input)| try_parse(target, &input).map(|crash| (run, input, crash)));
+
+        match crash {
+            None => report.push(format!(
+                "{}: {} runs OK ({} corpus samples)",
+                target.name,
+                corpus.len() + runs,
+                corpus.len()
+            )),
+            Some((run, input, crash)) => {
+                failures += 1;
+                let minimal_input = shrink(target, input, &crash);
+                report.push(format!(
+                    "{}: PANICKED on run {} at {}: {}\n  Minimal input: {:?}",
+                    target.name,
+                    run + 1,
+                    crash.location,
+                    crash.message,
+                    minimal_input
+                ));
+            }
+        }
+    }
+
+    let summary = report.join("\n");
+    if failures == 0 {
+        Ok(summary)
+    } else {
+        Err(format!("{summary}\n{failures} fuzz target(s) panicked"))
+    }
+}
+
+fn try_parse(target: &FuzzTarget, input: &str) -> Option<CaughtPanic> {
+    panics::catch(|| (target.parse)(input)).err()
+}
+
+fn mutate(rng: &mut Rng, corpus: &[String]) -> String {
+    if corpus.is_empty() || rng.chance(10) {
+        let len = rng.range(0, 64);
+        let bytes = (0..len).map(|_| random_byte(rng)).collect::<Vec<u8>>();
+        return String::from_utf8_lossy(&bytes).to_string();
+    }
+
+    let mut bytes = rng.pick(corpus).as_bytes().to_vec();
+
+    for _ in 0..rng.range(1, MAX_MUTATIONS + 1) {
+        let position = rng.range(0, bytes.len() + 1);
+
+        match rng.range(0, 6) {
+            0 if position < bytes.len() => bytes[position] = random_byte(rng),
+            1 => bytes.insert(position, random_byte(rng)),
+            2 if position < bytes.len() => {
+                let end = rng.range(position, bytes.len()) + 1;
+                bytes.drain(position..end);
+            }
+            3 if position < bytes.len() => {
+                let end = rng.range(position, bytes.len()) + 1;
+                let chunk = bytes[position..end].to_vec();
+                bytes.splice(position..position, chunk);
+            }
+            4 => bytes.truncate(position),
+            _ => {
+                let other = rng.pick(corpus).as_bytes();
+                let other_start = rng.range(0, other.len() + 1);
+                bytes.truncate(position);
+                bytes.extend_from_slice(&other[other_start..]);
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+fn random_byte(rng: &mut Rng) -> u8 {
+    if rng.chance(70) {
+        *rng.pick(INTERESTING_BYTES)
+    } else {
+        rng.range(0, 256) as u8
+    }
+}
+
+// Removes ever smaller chunks of characters for as long as the parser keeps panicking in the same
+// place with the same message. Any panic would do otherwise, and most inputs shrink to an empty
+// one panicking elsewhere. A different message from the same line, like another index out of
+// bounds, would no longer be the crash that gets reported.
+fn shrink(target: &FuzzTarget, input: String, crash: &CaughtPanic) -> String {
+    let mut smallest = input.chars().collect::<Vec<char>>();
+    let mut chunk_len = (smallest.len() / 2).max(1);
+
+    loop {
+        let mut start = 0;
+        while start < smallest.len() {
+            let end = (start + chunk_len).min(smallest.len());
+            let candidate = smallest[..start]
+                .iter()
+                .chain(smallest[end..].iter())
+                .collect::<String>();
+
+            let same_crash = try_parse(target, &candidate)
+                .is_some_and(|candidate_crash| candidate_crash == *crash);
+
+            if same_crash {
+                smallest = candidate.chars().collect();
+            } else {
+                start += chunk_len;
+            }
+        }
+
+        if chunk_len == 1 {
+            break smallest.into_iter().collect();
+        }
+        chunk_len /= 2;
+    }
+}
+
+fn whole_file(file: &str) -> Vec<String> {
+    vec![file.to_string()]
+}
+
+fn each_line(file: &str) -> Vec<String> {
+    puzzle_reader::lines(file)
+        .map(|line| line.to_string())
+        .collect()
+}
+
+fn first_word_of_each_line(file: &str) -> Vec<String> {
+    puzzle_reader::lines(file)
+        .filter_map(|line| line.split_whitespace().next())
+        .map(|word| word.to_string())
+        .collect()
+}
+
+fn to_strings(lines: Vec<&str>) -> Vec<String> {
+    lines.into_iter().map(|line| line.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shrinking_keeps_the_message_of_the_crash() {
+        let target = FuzzTarget {
+            name: "test",
+            day: 0,
+            corpus: whole_file,
+            parse: |input| {
+                if input.contains('x') {
+                    let message = if input.contains('y') { "x and y" } else { "x" };
+                    panic!("{message}");
+                }
+            },
+        };
+
+        let crash = try_parse(&target, "axbyc");
+        let minimal_input = crash
+            .as_ref()
+            .map(|crash| shrink(&target, "axbyc".to_string(), crash));
+        let minimal_crash = minimal_input
+            .as_ref()
+            .and_then(|input| try_parse(&target, input));
+
+        assert_eq!(minimal_input.as_deref(), Some("xy"));
+        assert_eq!(minimal_crash, crash);
+    }
+}
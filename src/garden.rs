@@ -1,3 +1,5 @@
+use crate::explain::{Column, Explanation};
+use crate::puzzle_reader;
 use std::collections::HashMap;
 
 #[derive(Debug)]
@@ -27,88 +29,36 @@ impl RangeMapping {
         }
     }
 
-    // Returns:
-    // - empty vector when no match found
-    // - vector with one element when the range being mapped is completely contained withing the
-    // mapping range
-    // - vector with two elements when the range being mapped overlaps partially. First element is
-    // the mapped range, second element is the unmapped rest of the initial range.
-    pub fn map_range(&self, range: &Range) -> Vec<Range> {
-        if self.overlaps(range) {
-            let is_offset_positive = self.source_start <= self.destination_start;
-
-            let offset = if is_offset_positive {
-                self.destination_start - self.source_start
-            } else {
-                self.source_start - self.destination_start
-            };
-
-            if self.is_contained_within(range) {
-                let mapped_range_start = if is_offset_positive {
-                    range.start + offset
-                } else {
-                    range.start - offset
-                };
-
-                let mapped_range = Range {
-                    start: mapped_range_start,
-                    len: range.len,
-                };
-                vec![mapped_range]
-            } else {
-                if range.start < self.source_start {
-                    // Range overlaps with tail
-                    let overlapping_length = range.start + range.len - self.source_start;
-
-                    let unchanged_range = Range {
-                        start: range.start,
-                        len: range.len - overlapping_length,
-                    };
-                    let mapped_range = Range {
-                        start: if is_offset_positive {
-                            self.source_start + offset
-                        } else {
-                            self.source_start - offset
-                        },
-                        len: overlapping_length,
-                    };
-                    vec![mapped_range, unchanged_range]
-                } else {
-                    // Range overlaps with beginning
-                    let mapping_range_end = self.source_start + self.len;
-                    let range_end = range.start + range.len;
-                    let overlapping_length = mapping_range_end - range.start;
-
-                    let mapped_range = Range {
-                        start: if is_offset_positive {
-                            range.start + offset
-                        } else {
-                            range.start - offset
-                        },
-                        len: overlapping_length,
-                    };
-                    let unchanged_range = Range {
-                        start: self.source_start + self.len,
-                        len: range_end - (self.source_start + self.len),
-                    };
-                    vec![mapped_range, unchanged_range]
-                }
-            }
-        } else {
-            Vec::new()
-        }
-    }
-
-    pub fn is_contained_within(&self, range: &Range) -> bool {
-        range.start >= self.source_start && range.start + range.len < self.source_start + self.len
-    }
-
-    fn overlaps(&self, range: &Range) -> bool {
+    // Splits the range into the part this mapping moves, if they overlap at all, and the parts
+    // before and after the mapping that it leaves as they are
+    pub fn map_range(&self, range: &Range) -> (Option<Range>, Vec<Range>) {
         let range_end = range.start + range.len;
-        let source_range_end = self.source_start + self.len;
+        let overlap_start = range.start.max(self.source_start);
+        let overlap_end = range_end.min(self.source_start + self.len);
+
+        if overlap_start >= overlap_end {
+            return (None, vec![*range]);
+        }
 
-        (range.start >= self.source_start && range.start < source_range_end)
-            || (range.start < self.source_start && range_end > self.source_start)
+        let mapped_range = Range {
+            start: self.destination_start + (overlap_start - self.source_start),
+            len: overlap_end - overlap_start,
+        };
+        let unchanged_ranges = [
+            Range {
+                start: range.start,
+                len: overlap_start - range.start,
+            },
+            Range {
+                start: overlap_end,
+                len: range_end - overlap_end,
+            },
+        ]
+        .into_iter()
+        .filter(|range| range.len > 0)
+        .collect();
+
+        (Some(mapped_range), unchanged_ranges)
     }
 }
 
@@ -126,8 +76,8 @@ struct Mappings {
 }
 
 impl Mappings {
-    pub fn parse(block: &str) -> Option<Mappings> {
-        let mut block_split = block.split("\n");
+    pub fn parse(block: Vec<&str>) -> Option<Mappings> {
+        let mut block_split = block.into_iter();
 
         let maybe_name = block_split.next().and_then(|line| {
             line.split(" map:").next().and_then(|name_str| {
@@ -167,34 +117,22 @@ impl Mappings {
         })
     }
 
+    // What no mapping moves keeps its numbers
     fn map_single_range(&self, range: &Range) -> Vec<Range> {
         let mut results: Vec<Range> = Vec::new();
-        let mut range_to_map = Some(range.clone());
+        let mut ranges_to_map = vec![*range];
 
         for mapping in self.range_mappings.iter() {
-            match range_to_map {
-                Some(r) => {
-                    let mapping_result = mapping.map_range(&r);
-                    if mapping_result.len() == 1 {
-                        let mapped_range = mapping_result.get(0).expect("Unexpected error");
-                        results.push(mapped_range.clone());
-                        range_to_map = None;
-                        break;
-                    } else if mapping_result.len() == 2 {
-                        let mapped_range = mapping_result.get(0).expect("Unexpected error");
-                        results.push(mapped_range.clone());
-                        range_to_map = mapping_result.get(1).copied();
-                    }
-                }
-                None => break,
+            let mut unmapped_ranges = Vec::new();
+            for range_to_map in ranges_to_map.iter() {
+                let (mapped_range, unchanged_ranges) = mapping.map_range(range_to_map);
+                results.extend(mapped_range);
+                unmapped_ranges.extend(unchanged_ranges);
             }
+            ranges_to_map = unmapped_ranges;
         }
 
-        match range_to_map {
-            Some(r) => results.push(r.clone()),
-            None => (),
-        }
-
+        results.extend(ranges_to_map);
         results
     }
 
@@ -206,17 +144,18 @@ impl Mappings {
     }
 }
 
-struct Almanac {
+pub struct Almanac {
     seeds: Vec<usize>,
     maps: HashMap<String, Mappings>,
 }
 
 impl Almanac {
     pub fn parse(file: &str) -> Option<Almanac> {
-        let mut blocks_split = file.split("\n\n");
+        let mut blocks_split = puzzle_reader::blocks(file);
 
-        let maybe_seeds = blocks_split.next().and_then(|line| {
-            let mut seeds_split = line.split("seeds: ");
+        let maybe_seeds = blocks_split.next().and_then(|block| {
+            let seeds_line = block.join(" ");
+            let mut seeds_split = seeds_line.split("seeds: ");
             seeds_split.nth(1).map(|seeds_str| {
                 seeds_str
                     .split_whitespace()
@@ -249,6 +188,8 @@ impl Almanac {
                 start: chunk[0],
                 len: chunk[1],
             })
+            // An empty range has no seeds, and so no location either
+            .filter(|range| range.len > 0)
             .collect::<Vec<Range>>();
         let mut category_name = "seed";
 
@@ -273,9 +214,27 @@ impl Almanac {
         }
     }
 
+    pub fn get_lowest_seed_destination_by_enumerating_ranges(&self) -> usize {
+        self.seeds
+            .chunks(2)
+            .flat_map(|chunk| chunk[0]..chunk[0] + chunk[1])
+            .map(|seed| self.get_location_for_seed(&seed))
+            .min()
+            .expect("Something went wrong - no result found")
+    }
+
     fn get_location_for_seed(&self, seed: &usize) -> usize {
+        self.get_mapping_chain(seed)
+            .last()
+            .map(|(_, value)| *value)
+            .unwrap_or(*seed)
+    }
+
+    // Every category the seed goes through on its way to a location, starting with the seed
+    fn get_mapping_chain(&self, seed: &usize) -> Vec<(&str, usize)> {
         let mut category_name = "seed";
         let mut source = *seed;
+        let mut chain = vec![(category_name, source)];
 
         loop {
             let map_for_category = self
@@ -285,9 +244,10 @@ impl Almanac {
             let destination = map_for_category
                 .get_destination_value(source)
                 .unwrap_or(source);
+            chain.push((&map_for_category.destination_name, destination));
 
             if map_for_category.destination_name == "location" {
-                break destination;
+                break chain;
             } else {
                 source = destination.clone();
                 category_name = &map_for_category.destination_name;
@@ -307,3 +267,31 @@ pub fn read_almanac_by_seed_ranges(file: &str) -> usize {
 
     almanac.get_lowest_seed_destination_for_seed_ranges()
 }
+
+pub fn explain_seeds(file: &str) -> Result<Explanation, String> {
+    let almanac = Almanac::parse(file).ok_or("Failed to parse input file")?;
+    let chains = almanac
+        .seeds
+        .iter()
+        .map(|seed| almanac.get_mapping_chain(seed))
+        .collect::<Vec<Vec<(&str, usize)>>>();
+
+    let columns = chains
+        .first()
+        .map(|chain| chain.iter().map(|(category, _)| Column::number(category)).collect())
+        .unwrap_or(vec![Column::number("seed")]);
+    let mut explanation = Explanation::new(columns);
+
+    for chain in chains.iter() {
+        explanation.add_row(chain.iter().map(|(_, value)| value.to_string()).collect());
+    }
+
+    Ok(explanation)
+}
+
+// Slow reference for `read_almanac_by_seed_ranges` which maps every seed of every range on its own
+pub fn read_almanac_by_enumerating_seed_ranges(file: &str) -> usize {
+    let almanac = Almanac::parse(file).expect("Failed to parse input file");
+
+    almanac.get_lowest_seed_destination_by_enumerating_ranges()
+}
@@ -1,141 +1,228 @@
-use regex::{Match, Regex};
+use crate::arithmetic::{self, OverflowError};
+use crate::puzzle_reader;
+
+// A number on the schematic, spanning `length` columns from `column` on its row. Rows and columns
+// count characters from 0, so lines of different lengths do not matter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SchematicNumber {
+    pub row: usize,
+    pub column: usize,
+    pub length: usize,
+    pub value: usize,
+}
 
-const NUMBER_REGEX_PATTERN: &str = r"\b\d+\b";
-const SYMBOL_REGEX_PATTERN: &str = r"[^\d^\.^\n]";
-const INVALID_REGEX_PATTERN: &str = "Invalid regex pattern";
+// Anything that is not a digit, a dot or blank
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Symbol {
+    pub row: usize,
+    pub column: usize,
+    pub character: char,
+}
 
-pub fn count_engine_parts(engine_schematic: &str) -> usize {
-    let mut sum: usize = 0;
-    let parts = get_engine_parts(engine_schematic);
-
-    for (line_number, (number_matches, symbol_matches)) in parts.iter().enumerate() {
-        let empty_symbols = Vec::new();
-        let prev_line_symbols = if line_number >= 1 {
-            parts
-                .get(line_number - 1)
-                .map(|(_, s)| s)
-                .unwrap_or(&empty_symbols)
-        } else {
-            &empty_symbols
-        };
-        let next_line_symbols = parts
-            .get(line_number + 1)
-            .map(|(_, s)| s)
-            .unwrap_or(&empty_symbols);
-
-        for number_match in number_matches.iter() {
-            let is_engine_part = !find_all_adjacent_matches(
-                number_match,
-                prev_line_symbols,
-                symbol_matches,
-                next_line_symbols,
-            )
-            .is_empty();
-
-            if is_engine_part {
-                let part_number = number_match.as_str().parse::<usize>().unwrap_or(0);
-                sum += part_number
+// Numbers and symbols are bucketed by row and sorted by column within it. The neighbours of
+// anything are then found with a binary search on the three rows around it, instead of going
+// through all of those rows.
+pub struct Schematic {
+    numbers: Vec<SchematicNumber>,
+    symbols: Vec<Symbol>,
+    // Where the numbers and symbols of each row start, with the total as the last entry
+    number_rows: Vec<usize>,
+    symbol_rows: Vec<usize>,
+}
+
+impl Schematic {
+    pub fn parse(engine_schematic: &str) -> Schematic {
+        let mut numbers: Vec<SchematicNumber> = Vec::new();
+        let mut symbols: Vec<Symbol> = Vec::new();
+        let mut number_rows: Vec<usize> = Vec::new();
+        let mut symbol_rows: Vec<usize> = Vec::new();
+
+        for (row, line) in puzzle_reader::lines(engine_schematic).enumerate() {
+            number_rows.push(numbers.len());
+            symbol_rows.push(symbols.len());
+            // The column it starts at and its value so far, none once it gets too large
+            let mut number: Option<(usize, Option<usize>)> = None;
+
+            for (column, character) in line.chars().chain(['.']).enumerate() {
+                if let Some(digit) = character.to_digit(10) {
+                    let (_, value) = number.get_or_insert((column, Some(0)));
+                    *value = value
+                        .and_then(|value| value.checked_mul(10))
+                        .and_then(|value| value.checked_add(digit as usize));
+                    continue;
+                }
+
+                if let Some((start, value)) = number.take() {
+                    numbers.push(SchematicNumber {
+                        row,
+                        column: start,
+                        length: column - start,
+                        // Too large to be a part number of any engine
+                        value: value.unwrap_or(0),
+                    });
+                }
+                if character != '.' && !character.is_whitespace() {
+                    symbols.push(Symbol {
+                        row,
+                        column,
+                        character,
+                    });
+                }
             }
         }
+        number_rows.push(numbers.len());
+        symbol_rows.push(symbols.len());
+
+        Schematic {
+            numbers,
+            symbols,
+            number_rows,
+            symbol_rows,
+        }
     }
 
-    sum
-}
+    pub fn numbers(&self) -> &[SchematicNumber] {
+        &self.numbers
+    }
 
-pub fn count_gear_ratio(engine_schematic: &str) -> usize {
-    let mut gear_ratios_sum: usize = 0;
-    let parts = get_engine_parts(engine_schematic);
-
-    for (line_number, (number_matches, symbol_matches)) in parts.iter().enumerate() {
-        let empty_vector = Vec::new();
-        let prev_line_numbers = if line_number >= 1 {
-            parts
-                .get(line_number - 1)
-                .map(|(n, _)| n)
-                .unwrap_or(&empty_vector)
-        } else {
-            &empty_vector
-        };
-        let next_line_numbers = parts
-            .get(line_number + 1)
-            .map(|(n, _)| n)
-            .unwrap_or(&empty_vector);
-
-        for symbol_match in symbol_matches.iter() {
-            let adjacent_matches = find_all_adjacent_matches(
-                symbol_match,
-                prev_line_numbers,
-                number_matches,
-                next_line_numbers,
-            );
-
-            let adjacent_numbers: Vec<usize> = adjacent_matches
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    pub fn symbols_next_to<'a>(&'a self, number: &'a SchematicNumber) -> impl Iterator<Item = &'a Symbol> + 'a {
+        self.rows_around(number.row).flat_map(move |row| {
+            let symbols = &self.symbols[self.symbol_rows[row]..self.symbol_rows[row + 1]];
+            let first = symbols.partition_point(|symbol| symbol.column + 1 < number.column);
+
+            symbols[first..]
                 .iter()
-                .filter_map(|m| m.as_str().parse::<usize>().ok())
-                .collect();
+                .take_while(|symbol| symbol.column <= number.column + number.length)
+        })
+    }
 
-            let gear_ratio: usize = match &adjacent_numbers[..] {
-                [a, b] => a * b,
-                _ => 0,
-            };
+    pub fn numbers_next_to<'a>(&'a self, symbol: &'a Symbol) -> impl Iterator<Item = &'a SchematicNumber> + 'a {
+        self.rows_around(symbol.row).flat_map(move |row| {
+            let numbers = &self.numbers[self.number_rows[row]..self.number_rows[row + 1]];
+            let first = numbers.partition_point(|number| number.column + number.length < symbol.column);
 
-            gear_ratios_sum += gear_ratio;
-        }
+            numbers[first..]
+                .iter()
+                .take_while(|number| number.column <= symbol.column + 1)
+        })
+    }
+
+    // The gear symbols with the required number of neighbours, together with those neighbours
+    pub fn gears<'a>(&'a self, rules: &'a GearRules) -> impl Iterator<Item = (&'a Symbol, Vec<&'a SchematicNumber>)> + 'a {
+        self.symbols
+            .iter()
+            .filter(|symbol| rules.is_gear_symbol(symbol))
+            .map(|symbol| (symbol, self.numbers_next_to(symbol).collect::<Vec<&SchematicNumber>>()))
+            .filter(|(_, numbers)| numbers.len() == rules.neighbours)
+    }
+
+    pub fn is_part_number(&self, number: &SchematicNumber) -> bool {
+        self.symbols_next_to(number).next().is_some()
+    }
+
+    fn rows_around(&self, row: usize) -> std::ops::RangeInclusive<usize> {
+        let last_row = self.number_rows.len().saturating_sub(2);
+        row.saturating_sub(1)..=(row + 1).min(last_row)
     }
-    gear_ratios_sum
 }
 
-fn get_engine_parts(engine_schematic: &str) -> Vec<(Vec<Match>, Vec<Match>)> {
-    let number_regex = Regex::new(NUMBER_REGEX_PATTERN).expect(INVALID_REGEX_PATTERN);
-    let symbol_regex = Regex::new(SYMBOL_REGEX_PATTERN).expect(INVALID_REGEX_PATTERN);
-
-    engine_schematic
-        .split("\n")
-        .into_iter()
-        .map(|line| {
-            let number_matches = number_regex
-                .captures_iter(line)
-                .filter_map(|cap| cap.get(0))
-                .collect();
-            let symbol_matches = symbol_regex
-                .captures_iter(line)
-                .filter_map(|cap| cap.get(0))
-                .collect();
-
-            (number_matches, symbol_matches)
-        })
-        .collect()
+pub fn count_engine_parts(engine_schematic: &str) -> usize {
+    let schematic = Schematic::parse(engine_schematic);
+
+    schematic
+        .numbers()
+        .iter()
+        .filter(|number| schematic.is_part_number(number))
+        .map(|number| number.value)
+        .sum()
 }
 
-fn find_all_adjacent_matches<'a>(
-    target_match: &Match,
-    prev_line_matches: &Vec<Match<'a>>,
-    this_line_matches: &Vec<Match<'a>>,
-    next_line_matches: &Vec<Match<'a>>,
-) -> Vec<Match<'a>> {
-    vec![
-        find_adjacent_matches(target_match, prev_line_matches),
-        find_adjacent_matches(target_match, this_line_matches),
-        find_adjacent_matches(target_match, next_line_matches),
-    ]
-    .into_iter()
-    .flatten()
-    .collect::<Vec<Match>>()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GearAggregation {
+    Product,
+    Sum,
+    Max,
 }
 
-fn find_adjacent_matches<'a>(target_match: &Match, matches: &Vec<Match<'a>>) -> Vec<Match<'a>> {
-    matches
-        .iter()
-        .filter(|m| are_matches_adjacent(target_match, m))
-        .copied()
-        .collect::<Vec<Match>>()
+impl GearAggregation {
+    pub fn parse(s: &str) -> Option<GearAggregation> {
+        match s {
+            "product" => Some(GearAggregation::Product),
+            "sum" => Some(GearAggregation::Sum),
+            "max" => Some(GearAggregation::Max),
+            _ => None,
+        }
+    }
+}
+
+// Which symbols are gears, how many numbers have to be next to one and how its ratio comes from
+// them. The puzzle's gear is a `*` next to exactly two numbers, multiplied together.
+pub struct GearRules {
+    // None when any symbol can be a gear
+    symbols: Option<Vec<char>>,
+    neighbours: usize,
+    aggregation: GearAggregation,
+}
+
+impl GearRules {
+    // `symbols` lists the gear symbols next to each other, `any` for all of them
+    pub fn build(symbols: &str, neighbours: usize, aggregation: &str) -> Result<GearRules, String> {
+        let symbols = match symbols {
+            "any" => None,
+            symbols => Some(symbols.chars().collect::<Vec<char>>()),
+        };
+        if let Some(symbol) = symbols
+            .iter()
+            .flatten()
+            .find(|symbol| symbol.is_ascii_digit() || **symbol == '.' || symbol.is_whitespace())
+        {
+            return Err(format!("'{symbol}' can not be a gear symbol"));
+        }
+        if symbols.as_ref().is_some_and(|symbols| symbols.is_empty()) {
+            return Err("There has to be at least one gear symbol, or any".to_string());
+        }
+        if neighbours == 0 {
+            return Err("A gear needs at least one neighbouring number".to_string());
+        }
+        let aggregation = GearAggregation::parse(aggregation).ok_or(format!(
+            "Unknown gear aggregation '{aggregation}', expected product, sum or max"
+        ))?;
+
+        Ok(GearRules {
+            symbols,
+            neighbours,
+            aggregation,
+        })
+    }
+
+    fn is_gear_symbol(&self, symbol: &Symbol) -> bool {
+        self.symbols
+            .as_ref()
+            .map(|symbols| symbols.contains(&symbol.character))
+            .unwrap_or(true)
+    }
+
+    fn ratio(&self, numbers: &[&SchematicNumber]) -> Result<u128, OverflowError> {
+        let values = numbers.iter().map(|number| number.value as u128);
+        match self.aggregation {
+            GearAggregation::Product => arithmetic::product(values, "gear ratio"),
+            GearAggregation::Sum => arithmetic::sum(values, "gear ratio"),
+            GearAggregation::Max => Ok(values.max().unwrap_or(0)),
+        }
+    }
 }
 
-fn are_matches_adjacent(match_a: &Match, match_b: &Match) -> bool {
-    // ...AAA...
-    // .BBXXXXXX
-    match_b.start() <= match_a.start() && match_b.end() >= match_a.start() ||
-    // ...AAA...
-    // ....BXXXX
-    match_b.start() >= match_a.start() && match_b.start() <= match_a.end()
+pub fn count_gear_ratio(engine_schematic: &str, rules: &GearRules) -> Result<u128, OverflowError> {
+    let schematic = Schematic::parse(engine_schematic);
+
+    let ratios = schematic
+        .gears(rules)
+        .map(|(_, numbers)| rules.ratio(&numbers))
+        .collect::<Result<Vec<u128>, OverflowError>>()?;
+
+    arithmetic::sum(ratios, "sum of gear ratios")
 }
@@ -0,0 +1,813 @@
+use crate::cube_conundrum::{CubeColor, CubeSet, CubesGame};
+use crate::puzzle_reader;
+use crate::random::Rng;
+use std::collections::{HashMap, HashSet};
+
+const DAY_ARG_PREFIX: &str = "--day=";
+const SIZE_ARG_PREFIX: &str = "--size=";
+const SEED_ARG_PREFIX: &str = "--seed=";
+const DEFAULT_SIZE: usize = 10;
+const DEFAULT_SEED: usize = 2023;
+
+type Generator = fn(&mut Rng, usize) -> String;
+
+pub fn run(program_args: &[String]) -> Result<String, String> {
+    let day = puzzle_reader::read_number_arg(program_args, DAY_ARG_PREFIX)?
+        .ok_or(format!("Missing argument: {DAY_ARG_PREFIX}"))?;
+    let size =
+        puzzle_reader::read_number_arg(program_args, SIZE_ARG_PREFIX)?.unwrap_or(DEFAULT_SIZE);
+    let seed =
+        puzzle_reader::read_number_arg(program_args, SEED_ARG_PREFIX)?.unwrap_or(DEFAULT_SEED);
+
+    if size == 0 {
+        return Err("The size must be at least 1".to_string());
+    }
+
+    let generate =
+        generator_for_day(day).ok_or(format!("There is no input generator for day {day}"))?;
+
+    Ok(generate(&mut Rng::new(seed as u64), size))
+}
+
+// The size is the number of lines or records for list-like inputs and the side length for grids
+fn generator_for_day(day: usize) -> Option<Generator> {
+    match day {
+        1 => Some(generate_calibration_document),
+        2 => Some(generate_games_log),
+        3 => Some(generate_engine_schematic),
+        4 => Some(generate_scratchcards),
+        5 => Some(generate_almanac),
+        6 => Some(generate_race_sheet),
+        7 => Some(generate_camel_hands),
+        8 => Some(generate_network),
+        9 => Some(generate_oasis_report),
+        10 => Some(generate_pipe_maze),
+        11 => Some(generate_galaxy_image),
+        12 => Some(generate_spring_records),
+        13 => Some(generate_mirror_patterns),
+        15 => Some(generate_initialization_sequence),
+        16 => Some(generate_contraption),
+        17 => Some(generate_heat_loss_map),
+        18 => Some(generate_dig_plan),
+        19 => Some(generate_workflows),
+        _ => None,
+    }
+}
+
+// Every line gets at least one numeric digit, which part one relies on
+pub fn generate_calibration_document(rng: &mut Rng, size: usize) -> String {
+    const DIGIT_WORDS: [&str; 9] = [
+        "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+    ];
+
+    (0..size)
+        .map(|_| {
+            let digit_position = rng.range(0, 6);
+            let tokens_count = rng.range(digit_position + 1, digit_position + 8);
+
+            (0..tokens_count)
+                .map(|token| match rng.range(0, 4) {
+                    _ if token == digit_position => random_digit(rng).to_string(),
+                    // Without its first letter a word can overlap the one before, like `twone`
+                    0 => DIGIT_WORDS[rng.range(0, 9)][rng.range(0, 2)..].to_string(),
+                    1 => random_digit(rng).to_string(),
+                    _ => {
+                        let len = rng.range(1, 5);
+                        random_letters(rng, len)
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn generate_games_log(rng: &mut Rng, size: usize) -> String {
+    const COLORS: [&str; 3] = ["red", "green", "blue"];
+
+    (1..=size)
+        .map(|game_id| {
+            let draws_count = rng.range(1, 7);
+            let mut draw = || {
+                let mut colors = COLORS.to_vec();
+                rng.shuffle(&mut colors);
+                let colors_count = rng.range(1, 4);
+
+                colors[..colors_count].iter().fold(CubeSet::new(), |cube_set, color| {
+                    let color = CubeColor::parse(color).expect("The colors are valid");
+                    cube_set.with(color, rng.range(1, 21))
+                })
+            };
+
+            let first_draw = draw();
+            (1..draws_count)
+                .fold(CubesGame::new(game_id, first_draw), |game, _| game.with_draw(draw()))
+                .to_string()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+pub fn generate_engine_schematic(rng: &mut Rng, size: usize) -> String {
+    const SYMBOLS: [char; 10] = ['*', '#', '+', '$', '/', '@', '%', '=', '&', '-'];
+    let side = size.max(3);
+
+    (0..side)
+        .map(|_| {
+            let mut row: Vec<char> = Vec::new();
+
+            while row.len() < side {
+                match rng.range(0, 100) {
+                    0..=11 => {
+                        let digits = rng.range(1, 4).min(side - row.len()) as u32;
+                        let number = rng.range(10_usize.pow(digits - 1), 10_usize.pow(digits));
+                        row.extend(number.to_string().chars());
+
+                        // Numbers written right next to each other would merge into one
+                        if row.len() < side {
+                            row.push(if rng.chance(20) {
+                                *rng.pick(&SYMBOLS)
+                            } else {
+                                '.'
+                            });
+                        }
+                    }
+                    12..=17 => row.push(*rng.pick(&SYMBOLS)),
+                    _ => row.push('.'),
+                }
+            }
+
+            row.into_iter().collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// No card wins copies of cards past the end of the stack
+pub fn generate_scratchcards(rng: &mut Rng, size: usize) -> String {
+    let winning_count = rng.range(3, 11);
+    let having_count = rng.range(winning_count, 26);
+    let format_numbers = |numbers: &[usize]| {
+        numbers
+            .iter()
+            .map(|n| format!("{:>2}", n))
+            .collect::<Vec<String>>()
+            .join(" ")
+    };
+
+    (0..size)
+        .map(|card_index| {
+            let mut numbers: Vec<usize> = (1..100).collect();
+            rng.shuffle(&mut numbers);
+
+            let max_matches = winning_count.min(size - card_index - 1);
+            let matches = if rng.chance(50) {
+                0
+            } else {
+                rng.range(0, max_matches + 1)
+            };
+
+            let winning = &numbers[..winning_count];
+            let mut having = winning[..matches].to_vec();
+            having.extend(&numbers[winning_count..winning_count + having_count - matches]);
+            rng.shuffle(&mut having);
+
+            format!(
+                "Card {:>3}: {} | {}",
+                card_index + 1,
+                format_numbers(winning),
+                format_numbers(&having)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// Seed ranges may overlap each other and the mappings, source ranges of a single map never do
+pub fn generate_almanac(rng: &mut Rng, size: usize) -> String {
+    const CATEGORIES: [&str; 8] = [
+        "seed",
+        "soil",
+        "fertilizer",
+        "water",
+        "light",
+        "temperature",
+        "humidity",
+        "location",
+    ];
+    let max_value = 10 + size * 10;
+
+    let seeds = (0..rng.range(1, size + 1))
+        .map(|_| format!("{} {}", rng.range(0, max_value), rng.range(1, size * 3 + 2)))
+        .collect::<Vec<String>>()
+        .join(" ");
+    let mut blocks = vec![format!("seeds: {seeds}")];
+
+    for categories in CATEGORIES.windows(2) {
+        let mut block = vec![format!("{}-to-{} map:", categories[0], categories[1])];
+        let mut source_start = rng.range(0, size * 2 + 1);
+
+        for _ in 0..rng.range(0, size + 1) {
+            let len = rng.range(1, size * 4 + 2);
+            block.push(format!(
+                "{} {} {}",
+                rng.range(0, max_value),
+                source_start,
+                len
+            ));
+            // Adjacent source ranges make for the trickiest splits
+            let gap = if rng.chance(30) {
+                0
+            } else {
+                rng.range(1, size * 2 + 1)
+            };
+            source_start += len + gap;
+        }
+        blocks.push(block.join("\n"));
+    }
+
+    blocks.join("\n\n")
+}
+
+pub fn generate_race_sheet(rng: &mut Rng, size: usize) -> String {
+    let races = (0..rng.range(1, size.min(4) + 1))
+        .map(|_| {
+            let time = rng.range(1, (size * 5 + 2).min(100));
+            let best_distance = rng.range(0, time * time / 4 + 2);
+            (time, best_distance)
+        })
+        .collect::<Vec<(usize, usize)>>();
+
+    let times = races
+        .iter()
+        .map(|(time, _)| time.to_string())
+        .collect::<Vec<String>>();
+    let distances = races
+        .iter()
+        .map(|(_, distance)| distance.to_string())
+        .collect::<Vec<String>>();
+
+    format!(
+        "Time: {}\nDistance: {}",
+        times.join(" "),
+        distances.join(" ")
+    )
+}
+
+fn generate_camel_hands(rng: &mut Rng, size: usize) -> String {
+    const CARDS: [char; 13] = [
+        '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A',
+    ];
+    let mut seen_hands: HashSet<String> = HashSet::new();
+    let mut lines: Vec<String> = Vec::new();
+
+    // There are only so many distinct hands
+    while lines.len() < size.min(100_000) {
+        // Drawing from a few cards makes pairs and better hands as common as in the real input
+        let cards_pool = (0..rng.range(1, 6))
+            .map(|_| *rng.pick(&CARDS))
+            .collect::<Vec<char>>();
+        let hand = (0..5).map(|_| *rng.pick(&cards_pool)).collect::<String>();
+
+        if seen_hands.insert(hand.clone()) {
+            lines.push(format!("{hand} {}", rng.range(1, 1001)));
+        }
+    }
+
+    lines.join("\n")
+}
+
+// Every ghost walks its own chain of nodes from a start node to an end node. A chain is a multiple
+// of the instructions long and the end node leads where the start node does, so every ghost keeps
+// circling through the same cycle.
+fn generate_network(rng: &mut Rng, size: usize) -> String {
+    let instructions = (0..rng.range(2, size.min(200) + 2))
+        .map(|_| *rng.pick(&['L', 'R']))
+        .collect::<Vec<char>>();
+    let mut inner_nodes_count = 0;
+
+    let chains = (0..rng.range(1, size.min(5) + 1))
+        .map(|ghost| {
+            let (start, end) = if ghost == 0 {
+                ("AAA".to_string(), "ZZZ".to_string())
+            } else {
+                let prefix = &node_name(ghost)[1..];
+                (format!("{prefix}A"), format!("{prefix}Z"))
+            };
+            let steps = instructions.len() * rng.range(1, size.min(10) + 1);
+
+            let mut chain = vec![start];
+            for _ in 1..steps {
+                inner_nodes_count += 1;
+                chain.push(node_name(inner_nodes_count));
+            }
+            chain.push(end);
+            chain
+        })
+        .collect::<Vec<Vec<String>>>();
+
+    let all_nodes = chains.iter().flatten().cloned().collect::<Vec<String>>();
+    let mut lines: Vec<String> = Vec::new();
+
+    for chain in chains.iter() {
+        let mut start_pair = (String::new(), String::new());
+
+        for (step, node) in chain[..chain.len() - 1].iter().enumerate() {
+            let next = chain[step + 1].clone();
+            let detour = rng.pick(&all_nodes).clone();
+            let pair = match instructions[step % instructions.len()] {
+                'L' => (next, detour),
+                _ => (detour, next),
+            };
+
+            lines.push(format!("{node} = ({}, {})", pair.0, pair.1));
+            if step == 0 {
+                start_pair = pair;
+            }
+        }
+
+        let end = chain.last().expect("A chain has at least two nodes");
+        lines.push(format!("{end} = ({}, {})", start_pair.0, start_pair.1));
+    }
+
+    rng.shuffle(&mut lines);
+    format!(
+        "{}\n\n{}",
+        instructions.iter().collect::<String>(),
+        lines.join("\n")
+    )
+}
+
+// Letters B to Y only, so that no inner node ever looks like a start or an end node
+fn node_name(index: usize) -> String {
+    [index / 576, index / 24, index]
+        .iter()
+        .map(|digit| (b'B' + (digit % 24) as u8) as char)
+        .collect()
+}
+
+// Values of low degree polynomials, so the differences always end up all zeroes
+fn generate_oasis_report(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let coefficients = (0..rng.range(1, 5))
+                .map(|_| rng.range(0, 11) as i64 - 5)
+                .collect::<Vec<i64>>();
+
+            (0..rng.range(5, 22) as i64)
+                .map(|x| {
+                    coefficients
+                        .iter()
+                        .rev()
+                        .fold(0, |value, coefficient| value * x + coefficient)
+                        .to_string()
+                })
+                .collect::<Vec<String>>()
+                .join(" ")
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn generate_pipe_maze(rng: &mut Rng, size: usize) -> String {
+    let side = size.max(1);
+    let squares_count = rng.range(1, side * side / 2 + 2);
+    let squares = grow_polyomino(rng, side, squares_count);
+    let outline = polyomino_outline(&squares);
+    let loop_tiles = outline.iter().copied().collect::<HashSet<(usize, usize)>>();
+    let start = *rng.pick(&outline);
+
+    let mut tiles = vec![vec!['.'; side + 1]; side + 1];
+    for (row, tiles_row) in tiles.iter_mut().enumerate() {
+        for (column, tile) in tiles_row.iter_mut().enumerate() {
+            // Junk pipes must not connect to the start, it would no longer be clear where the loop goes
+            let is_next_to_start = row.abs_diff(start.0) + column.abs_diff(start.1) == 1;
+            if !loop_tiles.contains(&(row, column)) && !is_next_to_start && rng.chance(40) {
+                *tile = *rng.pick(&['|', '-', 'L', 'J', '7', 'F']);
+            }
+        }
+    }
+
+    for (i, point) in outline.iter().enumerate() {
+        let previous = outline[(i + outline.len() - 1) % outline.len()];
+        let next = outline[(i + 1) % outline.len()];
+        tiles[point.0][point.1] = pipe_connecting(*point, previous, next);
+    }
+    tiles[start.0][start.1] = 'S';
+
+    tiles
+        .iter()
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn pipe_connecting(tile: (usize, usize), a: (usize, usize), b: (usize, usize)) -> char {
+    let connects = |towards: (i64, i64)| {
+        [a, b].iter().any(|neighbour| {
+            (
+                neighbour.0 as i64 - tile.0 as i64,
+                neighbour.1 as i64 - tile.1 as i64,
+            ) == towards
+        })
+    };
+
+    match (connects((-1, 0)), connects((1, 0)), connects((0, 1))) {
+        (true, true, _) => '|',
+        (true, _, true) => 'L',
+        (true, _, _) => 'J',
+        (_, true, true) => 'F',
+        (_, true, _) => '7',
+        _ => '-',
+    }
+}
+
+// Grows a random set of squares on a side x side grid one neighbour at a time. It never gets a
+// hole or two squares touching by their corners only, so its outline is a simple loop.
+fn grow_polyomino(rng: &mut Rng, side: usize, squares_count: usize) -> Vec<Vec<bool>> {
+    let mut squares = vec![vec![false; side]; side];
+    let first = (rng.range(0, side), rng.range(0, side));
+    squares[first.0][first.1] = true;
+    let mut grown = vec![first];
+
+    for _ in 0..squares_count * 20 {
+        if grown.len() >= squares_count {
+            break;
+        }
+
+        let (row, column) = *rng.pick(&grown);
+        let (row_step, column_step) = *rng.pick(&[(-1, 0), (1, 0), (0, -1), (0, 1)]);
+        let (next_row, next_column) = (row as i64 + row_step, column as i64 + column_step);
+
+        if is_taken(&squares, next_row, next_column) || !is_within(&squares, next_row, next_column)
+        {
+            continue;
+        }
+
+        let next = (next_row as usize, next_column as usize);
+        if keeps_outline_simple(&squares, next) {
+            squares[next.0][next.1] = true;
+            grown.push(next);
+        }
+    }
+
+    squares
+}
+
+fn keeps_outline_simple(squares: &[Vec<bool>], square: (usize, usize)) -> bool {
+    // Clockwise from the square above
+    const RING: [(i64, i64); 8] = [
+        (-1, 0),
+        (-1, 1),
+        (0, 1),
+        (1, 1),
+        (1, 0),
+        (1, -1),
+        (0, -1),
+        (-1, -1),
+    ];
+    let taken = RING.map(|(row_step, column_step)| {
+        is_taken(
+            squares,
+            square.0 as i64 + row_step,
+            square.1 as i64 + column_step,
+        )
+    });
+
+    // A diagonal neighbour with no shared side neighbour would pinch the outline into a figure eight
+    let pinches = (1..8)
+        .step_by(2)
+        .any(|i| taken[i] && !taken[i - 1] && !taken[(i + 1) % 8]);
+    // Touching the region in two separate places would enclose a hole
+    let touching_runs = (0..8).filter(|i| taken[*i] && !taken[(i + 7) % 8]).count();
+
+    !pinches && touching_runs == 1
+}
+
+fn is_within(squares: &[Vec<bool>], row: i64, column: i64) -> bool {
+    row >= 0 && column >= 0 && (row as usize) < squares.len() && (column as usize) < squares.len()
+}
+
+fn is_taken(squares: &[Vec<bool>], row: i64, column: i64) -> bool {
+    is_within(squares, row, column) && squares[row as usize][column as usize]
+}
+
+// Corners of the squares in the order of walking around the region, one unit step at a time
+fn polyomino_outline(squares: &[Vec<bool>]) -> Vec<(usize, usize)> {
+    let mut neighbours: HashMap<(usize, usize), Vec<(usize, usize)>> = HashMap::new();
+
+    for (row, squares_row) in squares.iter().enumerate() {
+        for (column, _) in squares_row.iter().enumerate().filter(|(_, taken)| **taken) {
+            let (r, c) = (row as i64, column as i64);
+            let sides = [
+                ((r - 1, c), (row, column), (row, column + 1)),
+                ((r + 1, c), (row + 1, column), (row + 1, column + 1)),
+                ((r, c - 1), (row, column), (row + 1, column)),
+                ((r, c + 1), (row, column + 1), (row + 1, column + 1)),
+            ];
+
+            for ((other_row, other_column), a, b) in sides {
+                if !is_taken(squares, other_row, other_column) {
+                    neighbours.entry(a).or_default().push(b);
+                    neighbours.entry(b).or_default().push(a);
+                }
+            }
+        }
+    }
+
+    let start = *neighbours
+        .keys()
+        .min()
+        .expect("The region must not be empty");
+    let mut outline = vec![start];
+    let mut previous = start;
+    let mut current = neighbours[&start][0];
+
+    while current != start {
+        outline.push(current);
+        let next = match neighbours[&current].as_slice() {
+            [a, b] if *a == previous => *b,
+            [a, _] => *a,
+            _ => panic!("Every corner of a simple outline has exactly two neighbours"),
+        };
+        previous = current;
+        current = next;
+    }
+
+    outline
+}
+
+fn generate_galaxy_image(rng: &mut Rng, size: usize) -> String {
+    let side = size.max(2);
+    let empty_rows = (0..side).map(|_| rng.chance(15)).collect::<Vec<bool>>();
+    let empty_columns = (0..side).map(|_| rng.chance(15)).collect::<Vec<bool>>();
+
+    empty_rows
+        .iter()
+        .map(|is_empty_row| {
+            empty_columns
+                .iter()
+                .map(|is_empty_column| {
+                    if !is_empty_row && !is_empty_column && rng.chance(8) {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// Every record is made from a real arrangement with some springs hidden, so it has at least one
+pub fn generate_spring_records(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let groups = (0..rng.range(1, size.min(5) + 1))
+                .map(|_| rng.range(1, 4))
+                .collect::<Vec<usize>>();
+
+            let mut springs = ".".repeat(rng.range(0, 3));
+            for (i, group) in groups.iter().enumerate() {
+                if i > 0 {
+                    springs.push_str(&".".repeat(rng.range(1, 3)));
+                }
+                springs.push_str(&"#".repeat(*group));
+            }
+            springs.push_str(&".".repeat(rng.range(0, 3)));
+
+            let record = springs
+                .chars()
+                .map(|spring| if rng.chance(40) { '?' } else { spring })
+                .collect::<String>();
+            let groups = groups
+                .iter()
+                .map(|group| group.to_string())
+                .collect::<Vec<String>>();
+
+            format!("{record} {}", groups.join(","))
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn generate_mirror_patterns(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            generate_mirror_pattern(rng)
+                .iter()
+                .map(|row| row.iter().collect::<String>())
+                .collect::<Vec<String>>()
+                .join("\n")
+        })
+        .collect::<Vec<String>>()
+        .join("\n\n")
+}
+
+// Point of incidence packs rows and columns into u32 numbers, so the patterns stay smaller than
+// that. Random patterns sometimes reflect by accident, those are drawn again.
+fn generate_mirror_pattern(rng: &mut Rng) -> Vec<Vec<char>> {
+    loop {
+        let (height, width) = (rng.range(3, 18), rng.range(3, 18));
+        let mut rows = (0..height)
+            .map(|_| (0..width).map(|_| *rng.pick(&['.', '#'])).collect())
+            .collect::<Vec<Vec<char>>>();
+
+        let reflection_line = rng.range(1, height);
+        for i in 0..reflection_line.min(height - reflection_line) {
+            rows[reflection_line + i] = rows[reflection_line - 1 - i].clone();
+        }
+
+        if rng.chance(50) {
+            rows = transpose(&rows);
+        }
+
+        if count_reflection_lines(&rows) + count_reflection_lines(&transpose(&rows)) == 1 {
+            break rows;
+        }
+    }
+}
+
+fn count_reflection_lines(rows: &[Vec<char>]) -> usize {
+    (1..rows.len())
+        .filter(|line| {
+            (0..*line.min(&(rows.len() - line))).all(|i| rows[line + i] == rows[line - 1 - i])
+        })
+        .count()
+}
+
+fn transpose(rows: &[Vec<char>]) -> Vec<Vec<char>> {
+    (0..rows[0].len())
+        .map(|column| rows.iter().map(|row| row[column]).collect())
+        .collect()
+}
+
+// Labels repeat, so lenses get replaced and removed as well as added
+fn generate_initialization_sequence(rng: &mut Rng, size: usize) -> String {
+    let labels = (0..size)
+        .map(|_| {
+            let len = rng.range(1, 7);
+            random_letters(rng, len)
+        })
+        .collect::<Vec<String>>();
+
+    (0..size * 4)
+        .map(|_| {
+            let label = rng.pick(&labels).clone();
+            if rng.chance(30) {
+                format!("{label}-")
+            } else {
+                format!("{label}={}", rng.range(1, 10))
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+fn generate_contraption(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| {
+                    if rng.chance(12) {
+                        *rng.pick(&['/', '\\', '|', '-'])
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn generate_heat_loss_map(rng: &mut Rng, size: usize) -> String {
+    let side = size.max(2);
+
+    (0..side)
+        .map(|_| (0..side).map(|_| random_digit(rng)).collect::<String>())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// Both parts dig the same random shape, stretched differently along the grid lines. Stretching
+// keeps the outline simple and the directions the same, so only the distances differ.
+fn generate_dig_plan(rng: &mut Rng, size: usize) -> String {
+    let side = size.max(1);
+    let squares_count = rng.range(1, side * side / 2 + 2);
+    let squares = grow_polyomino(rng, side, squares_count);
+    let outline = polyomino_outline(&squares);
+
+    let vertices = (0..outline.len())
+        .filter(|i| {
+            let previous = outline[(i + outline.len() - 1) % outline.len()];
+            let next = outline[(i + 1) % outline.len()];
+            previous.0 != next.0 && previous.1 != next.1
+        })
+        .map(|i| outline[i])
+        .collect::<Vec<(usize, usize)>>();
+
+    let short_rows = grid_line_positions(rng, side, 10);
+    let short_columns = grid_line_positions(rng, side, 10);
+    // Part two distances are written with five hex digits
+    let long_rows = grid_line_positions(rng, side, 0xFFFFF / side);
+    let long_columns = grid_line_positions(rng, side, 0xFFFFF / side);
+
+    (0..vertices.len())
+        .map(|i| {
+            let (from, to) = (vertices[i], vertices[(i + 1) % vertices.len()]);
+            let (direction, encoded_direction) = match (from.0.cmp(&to.0), from.1.cmp(&to.1)) {
+                (_, std::cmp::Ordering::Less) => ('R', 0),
+                (std::cmp::Ordering::Less, _) => ('D', 1),
+                (_, std::cmp::Ordering::Greater) => ('L', 2),
+                _ => ('U', 3),
+            };
+            let distance = |rows: &[usize], columns: &[usize]| {
+                rows[from.0].abs_diff(rows[to.0]) + columns[from.1].abs_diff(columns[to.1])
+            };
+
+            format!(
+                "{direction} {} (#{:05x}{encoded_direction})",
+                distance(&short_rows, &short_columns),
+                distance(&long_rows, &long_columns)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn grid_line_positions(rng: &mut Rng, side: usize, max_gap: usize) -> Vec<usize> {
+    let mut positions = vec![0];
+    for _ in 0..side {
+        let gap = rng.range(1, max_gap.max(1) + 1);
+        positions.push(positions.last().copied().unwrap_or(0) + gap);
+    }
+    positions
+}
+
+// Workflows only send parts further down the list, so every part ends up accepted or rejected
+fn generate_workflows(rng: &mut Rng, size: usize) -> String {
+    let mut names = vec!["in".to_string()];
+    let mut used_names = names.iter().cloned().collect::<HashSet<String>>();
+
+    while names.len() < size.min(10_000) {
+        let len = rng.range(2, 4);
+        let name = random_letters(rng, len);
+        if used_names.insert(name.clone()) {
+            names.push(name);
+        }
+    }
+
+    let mut workflows = (0..names.len())
+        .map(|i| {
+            let mut rules = (0..rng.range(0, 4))
+                .map(|_| {
+                    format!(
+                        "{}{}{}:{}",
+                        rng.pick(&['x', 'm', 'a', 's']),
+                        rng.pick(&['<', '>']),
+                        rng.range(1, 4001),
+                        pick_workflow_target(rng, &names[i + 1..])
+                    )
+                })
+                .collect::<Vec<String>>();
+            rules.push(pick_workflow_target(rng, &names[i + 1..]));
+
+            format!("{}{{{}}}", names[i], rules.join(","))
+        })
+        .collect::<Vec<String>>();
+    rng.shuffle(&mut workflows);
+
+    let parts = (0..size)
+        .map(|_| {
+            format!(
+                "{{x={},m={},a={},s={}}}",
+                rng.range(1, 4001),
+                rng.range(1, 4001),
+                rng.range(1, 4001),
+                rng.range(1, 4001)
+            )
+        })
+        .collect::<Vec<String>>();
+
+    format!("{}\n\n{}", workflows.join("\n"), parts.join("\n"))
+}
+
+fn pick_workflow_target(rng: &mut Rng, later_workflows: &[String]) -> String {
+    if later_workflows.is_empty() || rng.chance(30) {
+        rng.pick(&["A", "R"]).to_string()
+    } else {
+        rng.pick(later_workflows).clone()
+    }
+}
+
+fn random_digit(rng: &mut Rng) -> char {
+    (b'1' + rng.range(0, 9) as u8) as char
+}
+
+fn random_letters(rng: &mut Rng, len: usize) -> String {
+    (0..len)
+        .map(|_| (b'a' + rng.range(0, 26) as u8) as char)
+        .collect()
+}
@@ -1,7 +1,9 @@
+use crate::arithmetic::{self, OverflowError};
+use crate::puzzle_reader;
 use std::collections::HashMap;
 
 #[derive(Debug)]
-struct NodePair(String, String);
+pub struct NodePair(String, String);
 
 impl NodePair {
     pub fn left(&self) -> &str {
@@ -23,16 +25,16 @@ impl NodePair {
 }
 
 pub fn find_way(file: &str) -> usize {
-    let mut file_split = file.split("\n\n");
+    let mut file_split = puzzle_reader::blocks(file);
     let instructions: Vec<char> = file_split
         .next()
-        .map(|line| line.chars().collect())
+        .map(|block| block.concat().chars().collect())
         .unwrap_or(Vec::new());
     let mut nodes_map: HashMap<String, NodePair> = HashMap::new();
 
     match file_split.next() {
         Some(map_block) => {
-            for line in map_block.split("\n") {
+            for line in map_block {
                 let mut line_split = line.split(" = ");
                 let maybe_node = line_split.next();
                 let maybe_node_pair = line_split.next().and_then(NodePair::parse);
@@ -48,9 +50,6 @@ pub fn find_way(file: &str) -> usize {
         None => (),
     }
 
-    println!("Instructions: {:?}", instructions);
-    println!("Map: {:?}", nodes_map);
-
     const START_NODE: &str = "AAA";
     const END_NODE: &str = "ZZZ";
     let mut current_node: &str = START_NODE;
@@ -59,22 +58,16 @@ pub fn find_way(file: &str) -> usize {
     loop {
         let index = steps % instructions.len();
         steps = steps + 1;
-        println!("\nStep {steps}");
-        println!("Instruction index: {index}");
         let direction = instructions.get(index).expect("This should never happen");
-        println!("Instruction: {direction}");
         let current_node_pair = nodes_map
             .get(current_node)
             .expect("This should never happen");
-        println!("Current node pair {:?}", current_node_pair);
         let next_node = match direction {
             'L' => current_node_pair.left(),
             'R' => current_node_pair.right(),
             _ => panic!("Incorrect direction"),
         };
 
-        println!("Next node: {next_node}");
-
         if *next_node == *END_NODE {
             break;
         } else {
@@ -85,17 +78,17 @@ pub fn find_way(file: &str) -> usize {
     steps
 }
 
-pub fn find_way_ghosts(file: &str) -> usize {
-    let mut file_split = file.split("\n\n");
+pub fn find_way_ghosts(file: &str) -> Result<u128, OverflowError> {
+    let mut file_split = puzzle_reader::blocks(file);
     let instructions: Vec<char> = file_split
         .next()
-        .map(|line| line.chars().collect())
+        .map(|block| block.concat().chars().collect())
         .unwrap_or(Vec::new());
     let mut nodes_map: HashMap<String, NodePair> = HashMap::new();
 
     match file_split.next() {
         Some(map_block) => {
-            for line in map_block.split("\n") {
+            for line in map_block {
                 let mut line_split = line.split(" = ");
                 let maybe_node = line_split.next();
                 let maybe_node_pair = line_split.next().and_then(NodePair::parse);
@@ -144,14 +137,17 @@ pub fn find_way_ghosts(file: &str) -> usize {
         steps_arr.push(steps);
     }
 
-    steps_arr.iter().fold(1, |acc, elem| lcm(acc, *elem))
+    steps_arr
+        .iter()
+        .try_fold(1, |acc, elem| lcm(acc, *elem as u128))
 }
 
-pub fn lcm(a: usize, b: usize) -> usize {
-    (a * b) / gcd(a, b)
+// Dividing before multiplying keeps the intermediate result as small as the answer itself
+pub fn lcm(a: u128, b: u128) -> Result<u128, OverflowError> {
+    arithmetic::mul(a / gcd(a, b), b, "least common multiple of the ghost cycles")
 }
 
-pub fn gcd(a: usize, b: usize) -> usize {
+pub fn gcd(a: u128, b: u128) -> u128 {
     let mut next_a = b;
     let mut next_b = a % b;
 
@@ -1,4 +1,7 @@
+use crate::arithmetic::{self, OverflowError};
+use crate::puzzle_reader;
 use std::collections::HashMap;
+use std::io::BufRead;
 
 pub fn parse(line: &str, unfold_records: bool) -> (String, Vec<usize>) {
     let mut line_split = line.split_whitespace();
@@ -37,18 +40,33 @@ pub fn parse(line: &str, unfold_records: bool) -> (String, Vec<usize>) {
     (springs, groups)
 }
 
-pub fn calculate_arrangements(file: &str, unfold_records: bool) -> usize {
+pub fn calculate_arrangements(file: &str, unfold_records: bool) -> Result<u128, OverflowError> {
     let records = parse_input(file, unfold_records);
-    records.iter().fold(0, |acc, record| {
-        let (springs, groups) = record;
-        let mut cache: HashMap<String, usize> = HashMap::new();
-        let arrangements = solve(springs, groups, 0, &mut cache);
-        acc + arrangements
+    let arrangements = records
+        .iter()
+        .map(|(springs, groups)| count_arrangements(springs, groups) as u128);
+    arithmetic::sum(arrangements, "sum of arrangements")
+}
+
+pub fn calculate_arrangements_from_reader<R: BufRead>(file: R, unfold_records: bool) -> Result<u128, String> {
+    puzzle_reader::stream_lines(file).try_fold(0, |acc, line| {
+        let line = line?;
+        if line.is_empty() {
+            return Ok(acc);
+        }
+        let (springs, groups) = parse(&line, unfold_records);
+        let arrangements = count_arrangements(&springs, &groups) as u128;
+        arithmetic::add(acc, arrangements, "sum of arrangements").map_err(|e| e.to_string())
     })
 }
 
+fn count_arrangements(springs: &str, groups: &[usize]) -> usize {
+    let mut cache: HashMap<String, usize> = HashMap::new();
+    solve(springs, groups, 0, &mut cache)
+}
+
 fn parse_input(file: &str, unfold_records: bool) -> Vec<(String, Vec<usize>)> {
-    file.split("\n")
+    puzzle_reader::lines(file)
         .filter(|line| !line.is_empty())
         .map(|line| parse(line, unfold_records))
         .collect::<Vec<(String, Vec<usize>)>>()
@@ -0,0 +1,253 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+const MAX_HEADER_LINES: usize = 100;
+const MAX_BODY_SIZE: usize = 64 * 1024 * 1024;
+// A client that connects and then goes quiet would otherwise hold its thread forever
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Just enough HTTP/1.1 for one request per connection, both for serving and for calling others
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+}
+
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl Response {
+    pub fn json(status: u16, body: String) -> Response {
+        Response {
+            status,
+            content_type: "application/json",
+            body,
+        }
+    }
+
+    pub fn text(status: u16, body: String) -> Response {
+        Response {
+            status,
+            content_type: "text/plain",
+            body,
+        }
+    }
+}
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(header, _)| header.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+fn read_headers<R: BufRead>(reader: &mut R) -> Result<Vec<(String, String)>, String> {
+    let mut headers = Vec::new();
+
+    for _ in 0..MAX_HEADER_LINES {
+        let mut line = String::new();
+        reader
+            .read_line(&mut line)
+            .map_err(|e| format!("Failed to read the headers: {e}"))?;
+
+        let line = line.trim_end();
+        if line.is_empty() {
+            return Ok(headers);
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    Err("Too many headers".to_string())
+}
+
+fn content_length(headers: &[(String, String)]) -> Result<Option<usize>, String> {
+    let length = find_header(headers, "content-length")
+        .map(|value| {
+            value
+                .parse::<usize>()
+                .map_err(|_| "Invalid Content-Length".to_string())
+        })
+        .transpose()?;
+
+    match length {
+        Some(length) if length > MAX_BODY_SIZE => Err(format!(
+            "The body can not be larger than {MAX_BODY_SIZE} bytes"
+        )),
+        length => Ok(length),
+    }
+}
+
+pub fn read_request(stream: &TcpStream) -> Result<Request, String> {
+    stream
+        .set_read_timeout(Some(READ_TIMEOUT))
+        .map_err(|e| format!("Failed to set the read timeout: {e}"))?;
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader
+        .read_line(&mut request_line)
+        .map_err(|e| format!("Failed to read the request: {e}"))?;
+
+    let mut request_line_split = request_line.split_whitespace();
+    let (method, path) = match (request_line_split.next(), request_line_split.next()) {
+        (Some(method), Some(path)) => (method.to_string(), path.to_string()),
+        _ => return Err("Malformed request line".to_string()),
+    };
+
+    let headers = read_headers(&mut reader)?;
+    let mut body = vec![0; content_length(&headers)?.unwrap_or(0)];
+    reader
+        .read_exact(&mut body)
+        .map_err(|e| format!("Failed to read the request body: {e}"))?;
+
+    Ok(Request {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+pub fn write_response(stream: &mut TcpStream, response: &Response) -> std::io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        422 => "Unprocessable Entity",
+        _ => "Internal Server Error",
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        response.content_type,
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+// Sends a request to an `http://host[:port][/prefix]` base URL and returns the status and body.
+// There is no TLS without pulling in a library, an HTTPS site needs a local proxy in front of it.
+pub fn send(
+    base_url: &str,
+    method: &str,
+    path: &str,
+    headers: &[(&str, String)],
+    body: &str,
+) -> Result<(u16, String), String> {
+    let address = match base_url.strip_prefix("http://") {
+        Some(address) => address,
+        None if base_url.starts_with("https://") => {
+            return Err(format!(
+                "Can not connect to {base_url}, HTTPS is not supported. Use an http:// base_url, for example a local TLS proxy"
+            ))
+        }
+        None => return Err(format!("Invalid base URL {base_url}, expected http://host[:port]")),
+    };
+    let (host, prefix) = match address.split_once('/') {
+        Some((host, prefix)) => (host, format!("/{}", prefix.trim_end_matches('/'))),
+        None => (address, String::new()),
+    };
+    let host_and_port = match host.contains(':') {
+        true => host.to_string(),
+        false => format!("{host}:80"),
+    };
+
+    let mut stream = TcpStream::connect(&host_and_port)
+        .map_err(|e| format!("Failed to connect to {host_and_port}. Error was: {:?}", e))?;
+
+    let extra_headers = headers
+        .iter()
+        .map(|(name, value)| format!("{name}: {value}\r\n"))
+        .collect::<String>();
+    write!(
+        stream,
+        "{method} {prefix}{path} HTTP/1.1\r\nHost: {host}\r\nUser-Agent: aoc2023 (github.com/majcherpiotrek/aoc2023)\r\nConnection: close\r\n{extra_headers}Content-Length: {}\r\n\r\n{body}",
+        body.len()
+    )
+    .and_then(|_| stream.flush())
+    .map_err(|e| format!("Failed to send the request. Error was: {:?}", e))?;
+
+    let mut reader = BufReader::new(stream);
+    let mut status_line = String::new();
+    reader
+        .read_line(&mut status_line)
+        .map_err(|e| format!("Failed to read the response. Error was: {:?}", e))?;
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse::<u16>().ok())
+        .ok_or(format!(
+            "Malformed status line {:?}",
+            status_line.trim_end()
+        ))?;
+
+    let headers = read_headers(&mut reader)?;
+    let is_chunked = find_header(&headers, "transfer-encoding")
+        .map(|encoding| encoding.eq_ignore_ascii_case("chunked"))
+        .unwrap_or(false);
+
+    let body = if is_chunked {
+        read_chunked_body(&mut reader)?
+    } else {
+        let mut body = Vec::new();
+        match content_length(&headers)? {
+            Some(length) => {
+                body.resize(length, 0);
+                reader.read_exact(&mut body)
+            }
+            None => reader.read_to_end(&mut body).map(|_| ()),
+        }
+        .map_err(|e| format!("Failed to read the response body. Error was: {:?}", e))?;
+        body
+    };
+
+    String::from_utf8(body)
+        .map(|body| (status, body))
+        .map_err(|_| "The response is not UTF-8 text".to_string())
+}
+
+fn read_chunked_body<R: BufRead>(reader: &mut R) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+
+    loop {
+        let mut size_line = String::new();
+        reader
+            .read_line(&mut size_line)
+            .map_err(|e| format!("Failed to read the response body. Error was: {:?}", e))?;
+        let size_hex = size_line.trim().split(';').next().unwrap_or("");
+        let size = usize::from_str_radix(size_hex, 16)
+            .map_err(|_| format!("Invalid chunk size {size_hex:?}"))?;
+
+        if size == 0 {
+            return Ok(body);
+        }
+        if body.len() + size > MAX_BODY_SIZE {
+            return Err(format!(
+                "The body can not be larger than {MAX_BODY_SIZE} bytes"
+            ));
+        }
+
+        let mut chunk = vec![0; size + 2];
+        reader
+            .read_exact(&mut chunk)
+            .map_err(|e| format!("Failed to read the response body. Error was: {:?}", e))?;
+        body.extend_from_slice(&chunk[..size]);
+    }
+}
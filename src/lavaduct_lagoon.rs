@@ -1,83 +1,74 @@
-pub fn calculate_lagoon_surface(file: &str) -> i64 {
-    let dig_plan = file
-        .split("\n")
+use crate::arithmetic::{self, OverflowError};
+use crate::polygon::{Point, Polygon};
+use crate::puzzle_reader;
+
+pub fn calculate_lagoon_surface(file: &str) -> Result<i128, OverflowError> {
+    let dig_plan = puzzle_reader::lines(file)
         .filter(|l| !l.is_empty())
         .map(DigPlanEntry::parse_part_one)
         .collect::<Vec<DigPlanEntry>>();
 
-    let contour_length: i64 = dig_plan.iter().fold(0 as i64, |acc, entry| 
-      acc + i64::try_from(entry.steps).unwrap()
-    );
-
-    let corners = get_all_corners(&dig_plan);
-
-    // Wtf, Pick's theorem says it should be -1 but for some reason it works for +1 ¯\_(ツ)_/¯
-    // Need to do the abs because the surface might be negative if the corners ordered clockwise
-    calculate_surface(&corners).abs() + contour_length / 2 + 1
+    dig_lagoon(&dig_plan)?.enclosed_points()
 }
 
-pub fn calculate_lagoon_surface_part_two(file: &str) -> i64 {
-    let dig_plan = file
-        .split("\n")
+pub fn calculate_lagoon_surface_part_two(file: &str) -> Result<i128, OverflowError> {
+    let dig_plan = puzzle_reader::lines(file)
         .filter(|l| !l.is_empty())
         .map(DigPlanEntry::parse_part_two)
         .collect::<Vec<DigPlanEntry>>();
 
-    let contour_length: i64 = dig_plan.iter().fold(0 as i64, |acc, entry| 
-      acc + i64::try_from(entry.steps).unwrap()
-    );
-
-    let corners = get_all_corners(&dig_plan);
-
-    // Wtf, Pick's theorem says it should be -1 but for some reason it works for +1 ¯\_(ツ)_/¯
-    // Need to do the abs because the surface might be negative if the corners ordered clockwise
-    calculate_surface(&corners).abs() + contour_length / 2 + 1
+    dig_lagoon(&dig_plan)?.enclosed_points()
 }
 
-// Using the shoelace formula
-fn calculate_surface(corners: &Vec<(i64, i64)>) -> i64 {
-    let mut sum = 0;
-
-    for i in 0..corners.len() {
-        let j = i + 1;
+// Corners of the trench, with the first instruction starting at (0, 0) and up being positive y
+pub fn get_lagoon_outline(file: &str, decode_colors: bool) -> Result<Vec<Point>, OverflowError> {
+    let parse_entry = if decode_colors {
+        DigPlanEntry::parse_part_two
+    } else {
+        DigPlanEntry::parse_part_one
+    };
+    let dig_plan = puzzle_reader::lines(file)
+        .filter(|l| !l.is_empty())
+        .map(parse_entry)
+        .collect::<Vec<DigPlanEntry>>();
 
-        let (x_i, y_i) = corners[i];
+    Ok(dig_lagoon(&dig_plan)?.vertices().clone())
+}
 
-        let (x_j, y_j) = if j < corners.len() {
-            corners[j]
-        } else {
-            corners[0]
-        };
+fn dig_lagoon(dig_plan: &[DigPlanEntry]) -> Result<Polygon, OverflowError> {
+    let lagoon = Polygon::new(get_trench_points(dig_plan)?).expect("Dig plan must form a rectilinear loop");
 
-        sum += (x_i * y_j) - (x_j * y_i)
+    if !lagoon.is_simple() {
+        panic!("Dig plan trench crosses itself");
     }
 
-    sum / 2
+    Ok(lagoon)
 }
 
-fn get_all_corners(dig_plan: &Vec<DigPlanEntry>) -> Vec<(i64, i64)> {
-    let mut corners: Vec<(i64, i64)> = Vec::new();
-    let mut previous_direction = dig_plan.last().unwrap().direction;
-    let mut current_position = (0, 0);
+fn get_trench_points(dig_plan: &[DigPlanEntry]) -> Result<Vec<Point>, OverflowError> {
+    const OPERATION: &str = "trench coordinates";
+    let mut current_position: Point = (0, 0);
+    let mut points = vec![current_position];
+
     for entry in dig_plan.iter() {
-        let steps = i64::try_from(entry.steps).unwrap();
+        let steps = i64::try_from(entry.steps).map_err(|_| OverflowError {
+            operation: OPERATION,
+        })?;
+        let (x, y) = current_position;
 
-        if entry.direction != previous_direction {
-            corners.push(current_position.clone());
-        }
-        previous_direction = entry.direction;
         current_position = match entry.direction {
-            Direction::Up => (current_position.0, current_position.1 + steps),
-            Direction::Right => (current_position.0 + steps, current_position.1),
-            Direction::Down => (current_position.0, current_position.1 - steps),
-            Direction::Left => (current_position.0 - steps, current_position.1),
-        }
+            Direction::Up => (x, arithmetic::add(y, steps, OPERATION)?),
+            Direction::Right => (arithmetic::add(x, steps, OPERATION)?, y),
+            Direction::Down => (x, arithmetic::sub(y, steps, OPERATION)?),
+            Direction::Left => (arithmetic::sub(x, steps, OPERATION)?, y),
+        };
+        points.push(current_position);
     }
 
-    corners
+    Ok(points)
 }
 
-struct DigPlanEntry {
+pub struct DigPlanEntry {
     direction: Direction,
     steps: usize,
 }
@@ -1,7 +1,39 @@
+use crate::arithmetic::{self, OverflowError};
+use crate::puzzle_reader;
 use std::collections::HashMap;
+use std::io::BufRead;
 
 fn parse_input(file: &str) -> Vec<&str> {
-    file.trim().split(",").collect::<Vec<&str>>()
+    puzzle_reader::lines(file)
+        .flat_map(|line| line.split(","))
+        .filter(|step| !step.is_empty())
+        .collect::<Vec<&str>>()
+}
+
+// Reads the steps one comma separated segment at a time. A segment can still span a line break,
+// which separates steps just like in `parse_input`.
+fn stream_steps<R: BufRead>(file: R) -> impl Iterator<Item = Result<String, String>> {
+    file.split(b',').enumerate().flat_map(|(index, segment)| {
+        let steps = segment
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| String::from_utf8(bytes).map_err(|e| e.to_string()))
+            .map(|segment| {
+                let segment = match index {
+                    0 => segment.trim_start_matches(puzzle_reader::BYTE_ORDER_MARK).to_string(),
+                    _ => segment,
+                };
+                segment
+                    .split('\n')
+                    .map(|step| step.trim_end().to_string())
+                    .filter(|step| !step.is_empty())
+                    .collect::<Vec<String>>()
+            });
+
+        match steps {
+            Ok(steps) => steps.into_iter().map(Ok).collect::<Vec<Result<String, String>>>(),
+            Err(e) => vec![Err(format!("Failed to read the input. Error was: {e}"))],
+        }
+    })
 }
 
 fn calculate_hash(word: &str) -> u16 {
@@ -16,19 +48,23 @@ fn calculate_hash(word: &str) -> u16 {
     })
 }
 
-pub fn calculate_hash_for_sequence(file: &str) -> usize {
+pub fn calculate_hash_for_sequence(file: &str) -> Result<u128, OverflowError> {
     let sequence = parse_input(file);
 
-    sequence.iter().fold(0 as usize, |acc, elem| {
-        let hash = calculate_hash(elem);
-        acc + hash as usize
+    arithmetic::sum(sequence.iter().map(|elem| calculate_hash(elem) as u128), "sum of hashes")
+}
+
+pub fn calculate_hash_for_sequence_from_reader<R: BufRead>(file: R) -> Result<u128, String> {
+    stream_steps(file).try_fold(0, |acc, step| {
+        let hash = calculate_hash(&step?) as u128;
+        arithmetic::add(acc, hash, "sum of hashes").map_err(|e| e.to_string())
     })
 }
 
 type Lens = (String, usize);
 
 #[derive(Debug)]
-enum Instruction {
+pub enum Instruction {
     AddLens(String, usize),
     RemoveLens(String),
 }
@@ -119,40 +155,58 @@ impl Box {
     }
 }
 
-pub fn put_lenses_in_boxes(file: &str) -> usize {
+pub fn put_lenses_in_boxes(file: &str) -> Result<u128, OverflowError> {
     let instructions = parse_instructions(file);
 
     let mut boxes: Vec<Box> = (0..256).map(|_| Box::empty()).collect::<Vec<Box>>();
 
     for instruction in instructions.iter() {
-        match instruction {
-            add_lens_instruction @ Instruction::AddLens(label, focal_length) => {
-                let hash = add_lens_instruction.get_hash();
-                let box_to_update = boxes.get_mut(hash as usize).expect("Box must exist");
-                box_to_update.add_lens(&(label.clone(), *focal_length));
-            }
-            remove_lens_instruction @ Instruction::RemoveLens(label) => {
-                let hash = remove_lens_instruction.get_hash();
+        apply_instruction(&mut boxes, instruction);
+    }
 
-                let box_to_update = boxes.get_mut(hash as usize).expect("Box must exist");
-                box_to_update.remove_lens(label);
-            }
+    focusing_power(&boxes)
+}
+
+pub fn put_lenses_in_boxes_from_reader<R: BufRead>(file: R) -> Result<u128, String> {
+    let mut boxes: Vec<Box> = (0..256).map(|_| Box::empty()).collect::<Vec<Box>>();
+
+    for step in stream_steps(file) {
+        apply_instruction(&mut boxes, &Instruction::parse(&step?));
+    }
+
+    focusing_power(&boxes).map_err(|e| e.to_string())
+}
+
+fn apply_instruction(boxes: &mut [Box], instruction: &Instruction) {
+    match instruction {
+        add_lens_instruction @ Instruction::AddLens(label, focal_length) => {
+            let hash = add_lens_instruction.get_hash();
+            let box_to_update = boxes.get_mut(hash as usize).expect("Box must exist");
+            box_to_update.add_lens(&(label.clone(), *focal_length));
         }
+        remove_lens_instruction @ Instruction::RemoveLens(label) => {
+            let hash = remove_lens_instruction.get_hash();
 
-        println!("After {:?}", instruction);
-        let non_empty_boxes = boxes.iter().enumerate().filter(|(_, b)| !b.lenses_index.is_empty()).collect::<Vec<(usize, &Box)>>();
-        for (i, b) in non_empty_boxes.iter() {
-          println!("Box {}: {:?}", i, b.lenses);
-        } 
-        println!("\n");
+            let box_to_update = boxes.get_mut(hash as usize).expect("Box must exist");
+            box_to_update.remove_lens(label);
+        }
     }
+}
 
-    boxes.iter().enumerate().fold(0, |acc, (i, b)| {
-      let box_number_multiplier = i + 1;
-        let sum_lenses = b.lenses.iter().enumerate().fold(0, |acc_lenses, (lens_index, lens)| {
-            let lens_strength = box_number_multiplier * (lens_index + 1) * lens.1;
-            acc_lenses + lens_strength
-        });
-        acc + sum_lenses
-    })
+fn focusing_power(boxes: &[Box]) -> Result<u128, OverflowError> {
+    let lens_strengths = boxes
+        .iter()
+        .enumerate()
+        .flat_map(|(i, b)| {
+            let box_number_multiplier = (i + 1) as u128;
+            b.lenses.iter().enumerate().map(move |(lens_index, lens)| {
+                arithmetic::product(
+                    [box_number_multiplier, (lens_index + 1) as u128, lens.1 as u128],
+                    "focusing power of a lens",
+                )
+            })
+        })
+        .collect::<Result<Vec<u128>, OverflowError>>()?;
+
+    arithmetic::sum(lens_strengths, "total focusing power")
 }
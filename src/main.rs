@@ -1,4 +1,5 @@
-use std::env;
+use std::{env, process};
+use std::time::{Duration, Instant};
 
 
 mod puzzle_reader;
@@ -20,55 +21,235 @@ mod floor_will_be_lava;
 mod clumsy_crucible;
 mod lavaduct_lagoon;
 mod aplenty;
+mod polygon;
+mod arithmetic;
+mod render;
+mod export;
+mod random;
+mod differential;
+mod generator;
+mod fuzzing;
+mod panics;
+mod registry;
+mod config;
+mod json;
+mod cli;
+mod verify;
+mod explain;
+mod server;
+mod http;
+mod remote;
+mod stub_site;
 
-use crate::puzzle_reader::{PuzzleIdentifier, read_puzzle};
+use crate::config::{Config, OutputFormat};
+use crate::explain::Explanation;
+use crate::puzzle_reader::{read_puzzle, Puzzle, PuzzleIdentifier};
 
+const RENDER_FLAG: &str = "--render";
+const EXPORT_ARG_PREFIX: &str = "--export=";
+const EXPLAIN_FLAG: &str = "--explain";
+const REPORT_FLAG: &str = "--report";
+const STREAM_FLAG: &str = "--stream";
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    
-    match read_puzzle(&args) {
+
+    let (command, command_args) = match cli::parse(&args) {
+        Ok(cli::Invocation::Help(help)) => {
+            println!("{help}");
+            return;
+        },
+        Ok(cli::Invocation::Command(command, command_args)) => (command, command_args),
+        Err(e) => {
+            println!("{}", e);
+            process::exit(1);
+        }
+    };
+
+    let config = match Config::load(command_args) {
+        Ok(config) => config,
+        Err(e) => {
+            println!("Failed to load the configuration: {}", e);
+            process::exit(1);
+        }
+    };
+
+    match command.name {
+        cli::RUN_COMMAND => solve_puzzle(command_args, &config, false),
+        cli::BENCH_COMMAND => solve_puzzle(command_args, &config, true),
+        cli::VERIFY_COMMAND => print_report(verify::run(command_args, &config)),
+        cli::LIST_COMMAND => println!("{}", cli::list(&config)),
+        cli::GENERATE_COMMAND => match generator::run(command_args) {
+            Ok(generated_input) => println!("{generated_input}"),
+            Err(e) => {
+                println!("Failed to generate the input: {}", e);
+                process::exit(1);
+            }
+        },
+        cli::DIFFTEST_COMMAND => print_report(differential::run(command_args)),
+        cli::FUZZ_COMMAND => print_report(fuzzing::run(command_args, &config)),
+        cli::SERVE_COMMAND => print_report(server::run(command_args, &config)),
+        cli::FETCH_COMMAND => print_report(remote::fetch(command_args, &config)),
+        cli::SUBMIT_COMMAND => print_report(remote::submit(command_args, &config)),
+        cli::STUB_COMMAND => print_report(stub_site::run(command_args, &config)),
+        cli::CONFIG_COMMAND => match command_args.iter().any(|arg| arg == "show") {
+            true => println!("{}", config.show()),
+            false => {
+                println!("Missing config command, expected: config show");
+                process::exit(1);
+            }
+        },
+        cli::COMPLETIONS_COMMAND => match cli::completions(command_args.iter().find(|arg| !arg.starts_with("--")).map(|shell| shell.as_str()).unwrap_or("")) {
+            Ok(script) => println!("{script}"),
+            Err(e) => {
+                println!("{}", e);
+                process::exit(1);
+            }
+        },
+        _ => {},
+    }
+}
+
+fn print_report(result: Result<String, String>) {
+    match result {
+        Ok(report) => println!("{report}"),
+        Err(report) => {
+            println!("{report}");
+            process::exit(1);
+        }
+    }
+}
+
+fn solve_puzzle(args: &[String], config: &Config, bench: bool) {
+    if puzzle_reader::has_flag(args, STREAM_FLAG) {
+        return stream_puzzle(args, config);
+    }
+
+    match read_puzzle(args, config) {
         Ok(puzzle) => {
-            println!("Selected a puzzle for day {}, part {}", puzzle.identifier.day, puzzle.identifier.part);
-
-            let program_result = match puzzle.identifier {
-                PuzzleIdentifier { day: 1, part: 1 } => trebuchet::calibrate_using_digits_only(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 1, part: 2 } => trebuchet::calibrate_using_spelled_digits(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 2, part: 1 } => cube_conundrum::find_possible_games(&puzzle.input_data, 12, 13, 14).to_string(),
-                PuzzleIdentifier { day: 2, part: 2 } => cube_conundrum::power_of_minimal_possible_games(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 3, part: 1 } => gear_ratios::count_engine_parts(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 3, part: 2 } => gear_ratios::count_gear_ratio(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 4, part: 1 } => scratchcards::sum_scratchcard_points(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 4, part: 2 } => scratchcards::process_scratchcards(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 5, part: 1 } => garden::read_almanac_seed_by_seed(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 5, part: 2 } => garden::read_almanac_by_seed_ranges(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 6, part: 1 } => boat_races::calculate_race_winning_margin(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 6, part: 2 } => boat_races::calculate_winning_possibilities(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 7, part: 1 } => camel_cards::calculate_total_winning(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 7, part: 2 } => camel_cards::calculate_total_winning_with_jokers(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 8, part: 1 } => haunted_wasteland::find_way(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 8, part: 2 } => haunted_wasteland::find_way_ghosts(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 9, part: 1 } => mirage_maintenance::oasis_report(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 9, part: 2 } => mirage_maintenance::oasis_report_backwards(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 10, part: 1 } => pipe_maze::steps_to_farthest_loop_end(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 10, part: 2 } => pipe_maze::surface_inside_loop(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 11, part: 1 } => cosmic_expansion::calculate_distances_between_galaxies(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 12, part: 1 } => hot_springs::calculate_arrangements(&puzzle.input_data, false).to_string(),
-                PuzzleIdentifier { day: 12, part: 2 } => hot_springs::calculate_arrangements(&puzzle.input_data, true).to_string(),
-                PuzzleIdentifier { day: 15, part: 1 } => lens_library::calculate_hash_for_sequence(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 15, part: 2 } => lens_library::put_lenses_in_boxes(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 13, part: 1 } => point_of_incidence::count_reflections(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 16, part: 1 } => floor_will_be_lava::calculate_energized_tiles(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 16, part: 2 } => floor_will_be_lava::find_best_beam_entry(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 17, part: 1 } => clumsy_crucible::find_shortest_path(&puzzle.input_data).map(|n| n.to_string()).unwrap_or("None".to_string()),
-                PuzzleIdentifier { day: 17, part: 2 } => clumsy_crucible::find_shortest_path2(&puzzle.input_data).map(|n| n.to_string()).unwrap_or("None".to_string()),
-                PuzzleIdentifier { day: 18, part: 1 } => lavaduct_lagoon::calculate_lagoon_surface(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 18, part: 2 } => lavaduct_lagoon::calculate_lagoon_surface_part_two(&puzzle.input_data).to_string(),
-                PuzzleIdentifier { day: 19, part: 1 } => aplenty::sort_parts(&puzzle.input_data).to_string(),
-                _ => "Sorry, there is no solution for this puzzle yet ;(".to_string()
+            print_selected_puzzle(&puzzle.identifier, config);
+
+            if puzzle_reader::has_flag(args, RENDER_FLAG) {
+                let use_colors = render::stdout_supports_colors();
+                match render::render_puzzle(&puzzle.identifier, &puzzle.input_data, use_colors, config) {
+                    Ok(rendered_grid) => println!("{rendered_grid}"),
+                    Err(e) => println!("Failed to render the puzzle: {}", e),
+                }
+            }
+
+            if let Some(export_path) = puzzle_reader::read_text_arg(args, EXPORT_ARG_PREFIX) {
+                match export::export_puzzle(&puzzle.identifier, &puzzle.input_data, export_path) {
+                    Ok(()) => println!("Exported the puzzle to {export_path}"),
+                    Err(e) => println!("Failed to export the puzzle: {}", e),
+                }
+            }
+
+            let started = Instant::now();
+            let program_result = match registry::solve(&puzzle.identifier, &puzzle.input_data, config) {
+                Some(Ok(answer)) => answer,
+                Some(Err(e)) => format!("Failed to solve the puzzle: {e}"),
+                None => "Sorry, there is no solution for this puzzle yet ;(".to_string(),
             };
-            println!("{program_result}");
+            let elapsed = started.elapsed();
+
+            let mut tables = Vec::new();
+            if puzzle_reader::has_flag(args, EXPLAIN_FLAG) {
+                tables.push(("explanation", registry::explain(&puzzle.identifier, &puzzle.input_data, config)
+                    .unwrap_or(Err("There is no explanation for this puzzle yet".to_string()))));
+            }
+            if puzzle_reader::has_flag(args, REPORT_FLAG) {
+                tables.push(("report", registry::report(&puzzle.identifier, &puzzle.input_data, config)
+                    .unwrap_or(Err("There is no report for this puzzle yet".to_string()))));
+            }
+
+            print_answer(&puzzle.identifier, &program_result, elapsed, tables, config);
+
+            if bench {
+                println!("{}", benchmark(&puzzle, config));
+            }
         },
         Err(e) => println!("Failed to load the puzzle: {}", e),
     }
 }
+
+// Reads the input as the solver goes instead of loading it first, for inputs too large to hold
+fn stream_puzzle(args: &[String], config: &Config) {
+    let identifier = match puzzle_reader::read_puzzle_identifier(args, config.year) {
+        Ok(identifier) => identifier,
+        Err(e) => return println!("Failed to load the puzzle: {}", e),
+    };
+    let mut input = match puzzle_reader::open_puzzle_input_file(&config.input_dir, identifier.year, identifier.day, identifier.part) {
+        Ok(input) => input,
+        Err(e) => return println!("Failed to load the puzzle: {}", e),
+    };
+
+    print_selected_puzzle(&identifier, config);
+
+    let started = Instant::now();
+    let program_result = match registry::solve_stream(&identifier, &mut input, config) {
+        Some(Ok(answer)) => answer,
+        Some(Err(e)) => format!("Failed to read the puzzle input: {e}"),
+        None => "Sorry, this puzzle can not be solved from a stream yet ;(".to_string(),
+    };
+
+    print_answer(&identifier, &program_result, started.elapsed(), Vec::new(), config);
+}
+
+fn print_selected_puzzle(identifier: &PuzzleIdentifier, config: &Config) {
+    if config.format == OutputFormat::Text && config.verbosity > 0 {
+        println!("Selected a puzzle for {} day {}, part {}", identifier.year, identifier.day, identifier.part);
+    }
+}
+
+// Explanations and reports go before the answer in text and next to it in JSON
+fn print_answer(identifier: &PuzzleIdentifier, program_result: &str, elapsed: Duration, tables: Vec<(&str, Result<Explanation, String>)>, config: &Config) {
+    match config.format {
+        OutputFormat::Text => {
+            for (name, table) in tables.iter() {
+                match table {
+                    Ok(table) => println!("{}", table.to_table()),
+                    Err(e) => println!("Failed to produce the {name}: {}", e),
+                }
+            }
+            println!("{program_result}");
+            if config.verbosity > 1 {
+                println!("Solved in {:?}", elapsed);
+            }
+        },
+        OutputFormat::Json => {
+            let mut fields = vec![
+                ("year", identifier.year.to_string()),
+                ("day", identifier.day.to_string()),
+                ("part", identifier.part.to_string()),
+                ("answer", json::string(program_result)),
+            ];
+            if config.verbosity > 1 {
+                fields.push(("time_us", elapsed.as_micros().to_string()));
+            }
+            let error_names = tables.iter().map(|(name, _)| format!("{name}_error")).collect::<Vec<String>>();
+            for ((name, table), error_name) in tables.iter().zip(error_names.iter()) {
+                match table {
+                    Ok(table) => fields.push((name, table.to_json())),
+                    Err(e) => fields.push((error_name, json::string(e))),
+                }
+            }
+            println!("{}", json::object(&fields));
+        },
+    }
+}
+
+fn benchmark(puzzle: &Puzzle, config: &Config) -> String {
+    let timings: Vec<Duration> = (0..config.bench_iterations)
+        .map(|_| {
+            let started = Instant::now();
+            let _ = registry::solve(&puzzle.identifier, &puzzle.input_data, config);
+            started.elapsed()
+        })
+        .collect();
+
+    let min = timings.iter().min().copied().unwrap_or_default();
+    let max = timings.iter().max().copied().unwrap_or_default();
+    let mean = timings.iter().sum::<Duration>() / config.bench_iterations as u32;
+
+    format!("Benchmark over {} iterations: min {:?}, mean {:?}, max {:?}", config.bench_iterations, min, mean, max)
+}
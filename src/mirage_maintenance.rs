@@ -1,50 +1,52 @@
-pub fn oasis_report(file: &str) -> i64 {
-    let history_rows = file
-        .split("\n")
-        .map(|line| {
-            line.split_whitespace()
-                .flat_map(|s| s.parse::<i64>())
-                .collect::<Vec<i64>>()
-        })
+use crate::arithmetic::{self, OverflowError};
+use crate::puzzle_reader;
+use std::io::BufRead;
+
+pub fn oasis_report(file: &str) -> Result<i64, OverflowError> {
+    let history_rows = puzzle_reader::lines(file)
+        .map(parse_history_row)
         .filter(|row| !row.is_empty())
         .collect::<Vec<Vec<i64>>>();
 
-    println!("Decoded rows");
-    for row in history_rows.iter() {
-        println!("{:?}", row);
-    }
-    println!("\n");
-
-    history_rows.iter().fold(0, |acc, row| {
-        let value_prediciton = extrapolate_value_prediction(row.to_vec());
-        acc + value_prediciton
+    history_rows.iter().try_fold(0, |acc, row| {
+        let value_prediciton = extrapolate_value_prediction(row.to_vec())?;
+        arithmetic::add(acc, value_prediciton, "sum of predictions")
     })
 }
 
-pub fn oasis_report_backwards(file: &str) -> i64 {
-    let history_rows = file
-        .split("\n")
-        .map(|line| {
-            line.split_whitespace()
-                .flat_map(|s| s.parse::<i64>())
-                .collect::<Vec<i64>>()
-        })
+pub fn oasis_report_backwards(file: &str) -> Result<i64, OverflowError> {
+    let history_rows = puzzle_reader::lines(file)
+        .map(parse_history_row)
         .filter(|row| !row.is_empty())
         .collect::<Vec<Vec<i64>>>();
 
-    println!("Decoded rows");
-    for row in history_rows.iter() {
-        println!("{:?}", row);
-    }
-    println!("\n");
+    history_rows.iter().try_fold(0, |acc, row| {
+        let value_prediciton = extrapolate_value_prediction_backwards(row.to_vec())?;
+        arithmetic::add(acc, value_prediciton, "sum of predictions")
+    })
+}
 
-    history_rows.iter().fold(0, |acc, row| {
-        let value_prediciton = extrapolate_value_prediction_backwards(row.to_vec());
-        acc + value_prediciton
+pub fn oasis_report_from_reader<R: BufRead>(file: R, backwards: bool) -> Result<i64, String> {
+    puzzle_reader::stream_lines(file).try_fold(0, |acc, line| {
+        let row = parse_history_row(&line?);
+        let value_prediction = match (row.is_empty(), backwards) {
+            (true, _) => return Ok(acc),
+            (false, false) => extrapolate_value_prediction(row),
+            (false, true) => extrapolate_value_prediction_backwards(row),
+        };
+        value_prediction
+            .and_then(|value_prediction| arithmetic::add(acc, value_prediction, "sum of predictions"))
+            .map_err(|e| e.to_string())
     })
 }
 
-fn extrapolate_value_prediction(values: Vec<i64>) -> i64 {
+fn parse_history_row(line: &str) -> Vec<i64> {
+    line.split_whitespace()
+        .flat_map(|s| s.parse::<i64>())
+        .collect::<Vec<i64>>()
+}
+
+fn extrapolate_value_prediction(values: Vec<i64>) -> Result<i64, OverflowError> {
     let mut values_matrix = vec![values];
     let mut current_row_index = 0;
     loop {
@@ -52,15 +54,13 @@ fn extrapolate_value_prediction(values: Vec<i64>) -> i64 {
             .get(current_row_index)
             .expect("Invalid values matrix");
         let mut next_row = Vec::new();
-        println!("Row {}", current_row_index);
         for (i, elem) in current_row.iter().enumerate() {
             if i >= 1 {
-                let diff = elem - current_row.get(i - 1).expect("Unexpected error");
-                next_row.push(diff);
+                let previous = current_row.get(i - 1).expect("Unexpected error");
+                next_row.push(arithmetic::sub(*elem, *previous, "history differences")?);
             }
         }
         let has_all_zeroes = next_row.iter().all(|elem| *elem == 0);
-        println!("Pushing next row {:?}", next_row);
         values_matrix.push(next_row);
         if has_all_zeroes {
             break;
@@ -69,26 +69,16 @@ fn extrapolate_value_prediction(values: Vec<i64>) -> i64 {
         }
     }
 
-    for r in values_matrix.iter() {
-        println!("{:?}", r);
-    }
-
-    println!("\nCalculating placeholders ... \n");
     let mut next_row_last_element: i64 = 0;
     for row_index in (0..=values_matrix.len() - 1).into_iter().rev() {
         let current_row = values_matrix.get_mut(row_index).expect("");
-        println!("Current row: {:?}", current_row);
         let last_element_from_current_row = current_row.last().expect("Row must contain elements");
-        println!(
-            "{} + {}",
-            last_element_from_current_row, next_row_last_element
-        );
-        next_row_last_element = last_element_from_current_row + next_row_last_element;
+        next_row_last_element = arithmetic::add(*last_element_from_current_row, next_row_last_element, "prediction")?;
     }
-    next_row_last_element
+    Ok(next_row_last_element)
 }
 
-fn extrapolate_value_prediction_backwards(values: Vec<i64>) -> i64 {
+fn extrapolate_value_prediction_backwards(values: Vec<i64>) -> Result<i64, OverflowError> {
     let mut values_matrix = vec![values.into_iter().rev().collect()];
     let mut current_row_index = 0;
     loop {
@@ -96,15 +86,13 @@ fn extrapolate_value_prediction_backwards(values: Vec<i64>) -> i64 {
             .get(current_row_index)
             .expect("Invalid values matrix");
         let mut next_row = Vec::new();
-        println!("Row {}", current_row_index);
         for (i, elem) in current_row.iter().enumerate() {
             if i >= 1 {
-                let diff = elem - current_row.get(i - 1).expect("Unexpected error");
-                next_row.push(-1 * diff);
+                let previous = current_row.get(i - 1).expect("Unexpected error");
+                next_row.push(arithmetic::sub(*previous, *elem, "history differences")?);
             }
         }
         let has_all_zeroes = next_row.iter().all(|elem| *elem == 0);
-        println!("Pushing next row {:?}", next_row);
         values_matrix.push(next_row);
         if has_all_zeroes {
             break;
@@ -113,21 +101,11 @@ fn extrapolate_value_prediction_backwards(values: Vec<i64>) -> i64 {
         }
     }
 
-    for r in values_matrix.iter() {
-        println!("{:?}", r);
-    }
-
-    println!("\nCalculating placeholders ... \n");
     let mut next_row_last_element: i64 = 0;
     for row_index in (0..=values_matrix.len() - 1).into_iter().rev() {
         let current_row = values_matrix.get_mut(row_index).expect("");
-        println!("Current row: {:?}", current_row);
         let last_element_from_current_row = current_row.last().expect("Row must contain elements");
-        println!(
-            "{} - {}",
-            last_element_from_current_row, next_row_last_element
-        );
-        next_row_last_element = last_element_from_current_row - next_row_last_element;
+        next_row_last_element = arithmetic::sub(*last_element_from_current_row, next_row_last_element, "prediction")?;
     }
-    next_row_last_element
+    Ok(next_row_last_element)
 }
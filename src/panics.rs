@@ -0,0 +1,80 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::panic::{self, UnwindSafe};
+use std::sync::Once;
+
+static INSTALL_HOOK: Once = Once::new();
+
+thread_local! {
+    // Set while the thread runs `catch`, with where its last panic happened
+    static CATCHING: RefCell<Option<Option<String>>> = const { RefCell::new(None) };
+}
+
+// A panic `catch` kept from being printed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaughtPanic {
+    pub location: String,
+    pub message: String,
+}
+
+// Runs `f` and turns a panic in it into an error, without printing it. The panic hook is installed
+// once for the whole process and only keeps quiet on threads inside `catch`, the panics of any
+// other thread still reach the hook that was there before.
+pub fn catch<R, F: FnOnce() -> R + UnwindSafe>(f: F) -> Result<R, CaughtPanic> {
+    INSTALL_HOOK.call_once(|| {
+        let previous_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let location = info
+                .location()
+                .map(|location| format!("{}:{}", location.file(), location.line()));
+            let caught = CATCHING.with(|catching| match catching.borrow_mut().as_mut() {
+                Some(last_location) => {
+                    *last_location = location;
+                    true
+                }
+                None => false,
+            });
+            if !caught {
+                previous_hook(info);
+            }
+        }));
+    });
+
+    // Nested calls get the outer one back when they are done
+    let outer = CATCHING.with(|catching| catching.replace(Some(None)));
+    let result = panic::catch_unwind(f);
+    let location = CATCHING.with(|catching| catching.replace(outer)).flatten();
+
+    result.map_err(|payload| CaughtPanic {
+        location: location.unwrap_or("unknown location".to_string()),
+        message: panic_message(payload.as_ref()),
+    })
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or("unknown panic".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panics_are_caught_with_their_location_and_message() {
+        let line = line!() + 1;
+        let caught = catch(|| panic!("broken {}", 42));
+
+        assert_eq!(
+            caught,
+            Err(CaughtPanic {
+                location: format!("{}:{line}", file!()),
+                message: "broken 42".to_string(),
+            })
+        );
+        assert_eq!(catch(|| 7), Ok(7));
+    }
+}
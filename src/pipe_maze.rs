@@ -1,138 +1,64 @@
-use std::cmp::Ordering;
+use crate::arithmetic::OverflowError;
+use crate::polygon::{Point, Polygon};
+use crate::puzzle_reader;
+use std::collections::HashSet;
 
-pub fn surface_inside_loop(file: &str) -> usize {
+pub fn surface_inside_loop(file: &str) -> Result<i128, OverflowError> {
     let maze = Maze::parse(file);
-    let mut shortest_loop = find_shortest_loop_in_maze(&maze).expect("no loops found in maze");
-
-    println!("Shortest loop {:?}", shortest_loop);
-    shortest_loop.sort_by(|a, b| {
-        let row_ord = a.0.cmp(&b.0);
-        if row_ord == Ordering::Equal {
-            row_ord
-        } else {
-            a.1.cmp(&b.1)
-        }
-    });
-
-    let max_north = shortest_loop
-        .iter()
-        .min_by(|a, b| a.0.cmp(&b.0))
-        .expect("Failed to calculate bounding square");
-    let max_east = shortest_loop
-        .iter()
-        .max_by(|a, b| a.1.cmp(&b.1))
-        .expect("Failed to calculate bounding square");
-    let max_south = shortest_loop
-        .iter()
-        .max_by(|a, b| a.0.cmp(&b.0))
-        .expect("Failed to calculate bounding square");
-    let max_west = shortest_loop
-        .iter()
-        .min_by(|a, b| a.1.cmp(&b.1))
-        .expect("Failed to calculate bounding square");
-
-    let north_limit = max_north.0 + 1;
-    let east_limit = max_east.1;
-    let south_limit = max_south.0 - 1;
-    let west_limit = max_west.1;
-
-    println!("north {north_limit}");
-    println!("east {east_limit}");
-    println!("south {south_limit}");
-    println!("west {west_limit}");
-
-    let mut points_inside: Vec<(Position, MazeElement)> = Vec::new();
+    let shortest_loop = find_shortest_loop_in_maze(&maze).expect("no loops found in maze");
+    let loop_polygon = get_loop_polygon(&shortest_loop);
 
-    for row in north_limit..=south_limit {
-        println!("\nROW {row}");
-        let mut intersections_in_row: Vec<Position> = Vec::new();
+    loop_polygon.interior_points()
+}
 
-        let mut horizontal_line = false;
-        for start_col in west_limit..=east_limit {
-            let point_to_verify = Position(row, start_col);
-            let element_to_verify = maze.get_element(&point_to_verify).unwrap();
-            if *element_to_verify == MazeElement::Ground {
-                continue;
-            }
-            println!("Verify {:?}, {:?}", point_to_verify, element_to_verify);
-            let is_part_of_the_loop = shortest_loop.contains(&point_to_verify);
-            if is_part_of_the_loop {
-                println!("is in the loop");
-                if element_to_verify.is_corner() && !horizontal_line {
-                    horizontal_line = true;
-                } else if element_to_verify.is_corner() && horizontal_line {
-                    horizontal_line = false;
-                    intersections_in_row.push(point_to_verify);
-                } else if element_to_verify.is_vertical_pipe() {
-                    intersections_in_row.push(point_to_verify);
-                } else if *element_to_verify == MazeElement::StartingPosition {
-                    let preceeding_element = if start_col > 0 {
-                        maze.get_element(&Position(row, start_col - 1))
-                    } else {
-                        None
-                    };
-                    let succeeding_element = maze.get_element(&Position(row, start_col + 1));
+pub fn steps_to_farthest_loop_end(file: &str) -> Result<i128, OverflowError> {
+    let maze = Maze::parse(file);
+    let shortest_loop = find_shortest_loop_in_maze(&maze).expect("no loops found in maze");
+    let loop_polygon = get_loop_polygon(&shortest_loop);
 
-                    let is_preceeding_horizontal = preceeding_element
-                        .map(|el| el.is_horizontal_pipe())
-                        .unwrap_or(false);
+    loop_polygon.boundary_points().map(|loop_length| loop_length / 2)
+}
 
-                    let is_succeeding_horizontal = succeeding_element
-                        .map(|el| el.is_horizontal_pipe())
-                        .unwrap_or(false);
+// The tiles of the main loop, in the order the loop goes through them, and the tiles it encloses
+pub struct LoopTiles {
+    pub loop_tiles: Vec<(usize, usize)>,
+    pub enclosed_tiles: Vec<(usize, usize)>,
+}
 
-                    let is_corner = (!is_preceeding_horizontal && is_succeeding_horizontal)
-                        || (is_preceeding_horizontal && !is_succeeding_horizontal);
-                    let is_vertical = !is_preceeding_horizontal && !is_succeeding_horizontal;
+pub fn find_loop_tiles(file: &str) -> LoopTiles {
+    let maze = Maze::parse(file);
+    let shortest_loop = find_shortest_loop_in_maze(&maze).expect("no loops found in maze");
+    let loop_polygon = get_loop_polygon(&shortest_loop);
+    let loop_tiles = shortest_loop
+        .iter()
+        .map(|position| (position.0, position.1))
+        .collect::<HashSet<(usize, usize)>>();
 
-                    if is_corner && !horizontal_line {
-                        horizontal_line = true;
-                    } else if is_corner && horizontal_line {
-                        horizontal_line = false;
-                        intersections_in_row.push(point_to_verify);
-                    } else if is_vertical {
-                        intersections_in_row.push(point_to_verify);
-                    }
-                }
-            }
-        }
-        println!("Intersections in row {:?}", intersections_in_row);
-        for start_col in (west_limit + 1)..=(east_limit - 1) {
-            println!("\nCOL {start_col}");
-            let point_to_verify = Position(row, start_col);
-            let element_to_verify = maze.get_element(&point_to_verify).unwrap();
-            if *element_to_verify != MazeElement::Ground {
-                continue;
-            }
-            let num_of_intersections_before =
-                intersections_in_row
-                    .iter()
-                    .fold(0, |acc, p| if p.1 < start_col { acc + 1 } else { acc });
-            let num_of_intersections_after =
-                intersections_in_row
-                    .iter()
-                    .fold(0, |acc, p| if p.1 > start_col { acc + 1 } else { acc });
+    let mut enclosed_tiles: Vec<(usize, usize)> = Vec::new();
+    for (row, maze_row) in maze.maze.iter().enumerate() {
+        for column in 0..maze_row.len() {
+            let is_enclosed = !loop_tiles.contains(&(row, column))
+                && loop_polygon.contains((row as i64, column as i64));
 
-            if num_of_intersections_before != 0
-                && num_of_intersections_before % 2 != 0
-                && num_of_intersections_after != 0
-                && num_of_intersections_after % 2 != 0
-            {
-                points_inside.push((point_to_verify, element_to_verify.clone()));
+            if is_enclosed {
+                enclosed_tiles.push((row, column));
             }
         }
     }
 
-    println!("Points inside {:?}", points_inside);
-
-    points_inside.len()
+    LoopTiles {
+        loop_tiles: shortest_loop.iter().map(|position| (position.0, position.1)).collect(),
+        enclosed_tiles,
+    }
 }
 
-pub fn steps_to_farthest_loop_end(file: &str) -> usize {
-    let maze = Maze::parse(file);
-    let shortest_loop = find_shortest_loop_in_maze(&maze).expect("no loops found in maze");
+fn get_loop_polygon(shortest_loop: &[Position]) -> Polygon {
+    let loop_points = shortest_loop
+        .iter()
+        .map(|position| (position.0 as i64, position.1 as i64))
+        .collect::<Vec<Point>>();
 
-    shortest_loop.len() / 2
+    Polygon::new(loop_points).expect("Maze loop must be made of pipes")
 }
 
 fn find_shortest_loop_in_maze(maze: &Maze) -> Option<Vec<Position>> {
@@ -148,7 +74,7 @@ fn find_shortest_loop_in_maze(maze: &Maze) -> Option<Vec<Position>> {
     paths.into_iter().min_by(|a, b| a.len().cmp(&b.len()))
 }
 
-struct Maze {
+pub struct Maze {
     maze: Vec<Vec<MazeElement>>,
     maze_start: Position,
 }
@@ -157,7 +83,7 @@ impl Maze {
     pub fn parse(file: &str) -> Maze {
         let mut maybe_maze_start: Option<Position> = None;
         let mut maze: Vec<Vec<MazeElement>> = Vec::new();
-        for (row, line) in file.split("\n").enumerate() {
+        for (row, line) in puzzle_reader::lines(file).enumerate() {
             let mut maze_row: Vec<MazeElement> = Vec::new();
             for (column, c) in line.chars().enumerate() {
                 let maze_element = MazeElement::parse(c);
@@ -241,7 +167,7 @@ impl Maze {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum MazeElement {
+pub enum MazeElement {
     Pipe { end_a: Direction, end_b: Direction },
     Ground,
     StartingPosition,
@@ -280,36 +206,6 @@ impl MazeElement {
         }
     }
 
-    pub fn is_corner(&self) -> bool {
-        match self {
-            MazeElement::Pipe { end_a, end_b } => {
-                *end_a == Direction::North && *end_b == Direction::East
-                    || *end_a == Direction::North && *end_b == Direction::West
-                    || *end_a == Direction::South && *end_b == Direction::East
-                    || *end_a == Direction::South && *end_b == Direction::West
-            }
-            _ => false,
-        }
-    }
-
-    pub fn is_horizontal_pipe(&self) -> bool {
-        match self {
-            MazeElement::Pipe { end_a, end_b } => {
-                *end_a == Direction::East && *end_b == Direction::West
-            }
-            _ => false,
-        }
-    }
-
-    pub fn is_vertical_pipe(&self) -> bool {
-        match self {
-            MazeElement::Pipe { end_a, end_b } => {
-                *end_a == Direction::North && *end_b == Direction::South
-            }
-            _ => false,
-        }
-    }
-
     pub fn go_through_pipe(&self, enter_from: &Direction) -> Option<Direction> {
         //println!("Entering a pipe {:?} from {:?}", self, enter_from);
         match self {
@@ -328,7 +224,7 @@ impl MazeElement {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum Direction {
+pub enum Direction {
     North,
     East,
     South,
@@ -347,7 +243,7 @@ impl Direction {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
-struct Position(usize, usize);
+pub struct Position(usize, usize);
 
 impl Position {
     pub fn move_in_direction(&self, direction: &Direction) -> Option<Position> {
@@ -1,16 +1,13 @@
+use crate::explain::{Column, Explanation};
+use crate::puzzle_reader;
+
 fn parse_input(file: &str) -> Vec<(Vec<u32>, Vec<u32>)> {
-    file.split("\n\n")
-        .filter(|b| !b.is_empty())
-        .map(|block| parse_pattern(block))
+    puzzle_reader::blocks(file)
+        .map(parse_pattern)
         .collect::<Vec<(Vec<u32>, Vec<u32>)>>()
 }
 
-fn parse_pattern(block: &str) -> (Vec<u32>, Vec<u32>) {
-    let pattern_rows = block
-        .split("\n")
-        .filter(|line| !line.is_empty())
-        .collect::<Vec<&str>>();
-
+fn parse_pattern(pattern_rows: Vec<&str>) -> (Vec<u32>, Vec<u32>) {
     let rows = pattern_rows
         .iter()
         .map(|line| line_to_number(line))
@@ -94,21 +91,44 @@ pub fn count_reflections(file: &str) -> usize {
         let point_of_reflection_horizontal = find_point_of_reflection(rows);
         let point_of_reflection_vertical = find_point_of_reflection(columns);
 
-        let pattern_sum = match (point_of_reflection_vertical, point_of_reflection_horizontal) {
-            (Some(vertical_reflection_point), None) => vertical_reflection_point,
-            (None, Some(horizontal_reflection)) => 100 * (horizontal_reflection),
-            (Some(vertical_reflection_point), Some(horizontal_reflection)) => {
-                vertical_reflection_point + 100 * horizontal_reflection
-            }
-            _ => 0,
-        };
-        println!("Pattern sum: {pattern_sum}");
+        let pattern_sum = pattern_value(point_of_reflection_vertical, point_of_reflection_horizontal);
         sum += pattern_sum;
-        println!("Horizontal: {:?}", point_of_reflection_horizontal);
-        println!("Rows: {:?}\n", rows);
-        println!("Vertical: {:?}", point_of_reflection_vertical);
-        println!("Columns: {:?}", columns);
-        println!("\n\n");
     }
     sum
 }
+
+// One row per pattern with the columns left of the vertical line and the rows above the horizontal one
+pub fn explain_reflections(file: &str) -> Explanation {
+    let mut explanation = Explanation::new(vec![
+        Column::number("pattern"),
+        Column::number("vertical"),
+        Column::number("horizontal"),
+        Column::number("value"),
+    ]);
+
+    for (index, (rows, columns)) in parse_input(file).iter().enumerate() {
+        let horizontal = find_point_of_reflection(rows);
+        let vertical = find_point_of_reflection(columns);
+        let line = |point: Option<usize>| point.map(|p| p.to_string()).unwrap_or("-".to_string());
+
+        explanation.add_row(vec![
+            (index + 1).to_string(),
+            line(vertical),
+            line(horizontal),
+            pattern_value(vertical, horizontal).to_string(),
+        ]);
+    }
+
+    explanation
+}
+
+fn pattern_value(vertical: Option<usize>, horizontal: Option<usize>) -> usize {
+    match (vertical, horizontal) {
+        (Some(vertical_reflection_point), None) => vertical_reflection_point,
+        (None, Some(horizontal_reflection)) => 100 * (horizontal_reflection),
+        (Some(vertical_reflection_point), Some(horizontal_reflection)) => {
+            vertical_reflection_point + 100 * horizontal_reflection
+        }
+        _ => 0,
+    }
+}
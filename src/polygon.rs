@@ -0,0 +1,306 @@
+use crate::arithmetic::{self, OverflowError};
+
+pub type Point = (i64, i64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Clockwise,
+    CounterClockwise,
+    Degenerate,
+}
+
+// A closed rectilinear polygon on the integer lattice. Every edge is horizontal or vertical and
+// the last vertex connects back to the first one.
+#[derive(Debug, Clone)]
+pub struct Polygon {
+    vertices: Vec<Point>,
+}
+
+impl Polygon {
+    pub fn new(points: Vec<Point>) -> Result<Polygon, String> {
+        let mut vertices: Vec<Point> = Vec::new();
+
+        for point in points.into_iter() {
+            if vertices.last() != Some(&point) {
+                vertices.push(point);
+            }
+        }
+
+        while vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+
+        for i in 0..vertices.len() {
+            let (a, b) = (vertices[i], vertices[(i + 1) % vertices.len()]);
+            if a.0 != b.0 && a.1 != b.1 {
+                return Err(format!("Edge from {:?} to {:?} is not axis-aligned", a, b));
+            }
+        }
+
+        Ok(Polygon {
+            vertices: remove_collinear_vertices(vertices),
+        })
+    }
+
+    pub fn vertices(&self) -> &Vec<Point> {
+        &self.vertices
+    }
+
+    // Using the shoelace formula. Positive when the vertices go counter-clockwise in a y-up frame.
+    // The cross products of i64 coordinates always fit in i128, their sum not necessarily.
+    pub fn signed_area(&self) -> Result<i128, OverflowError> {
+        const OPERATION: &str = "polygon area";
+        let mut sum: i128 = 0;
+
+        for (i, j) in self.edge_indices() {
+            let (x_i, y_i) = self.vertices[i];
+            let (x_j, y_j) = self.vertices[j];
+            let cross_product = arithmetic::sub(
+                x_i as i128 * y_j as i128,
+                x_j as i128 * y_i as i128,
+                OPERATION,
+            )?;
+            sum = arithmetic::add(sum, cross_product, OPERATION)?;
+        }
+
+        // A rectilinear polygon with integer vertices always has an integer area
+        Ok(sum / 2)
+    }
+
+    pub fn area(&self) -> Result<i128, OverflowError> {
+        let signed_area = self.signed_area()?;
+
+        Ok(match orientation_of(signed_area) {
+            Orientation::CounterClockwise => signed_area,
+            Orientation::Clockwise => -signed_area,
+            Orientation::Degenerate => 0,
+        })
+    }
+
+    pub fn orientation(&self) -> Result<Orientation, OverflowError> {
+        self.signed_area().map(orientation_of)
+    }
+
+    // Number of lattice points lying on the edges. For axis-aligned edges that is simply the
+    // edge length, as every unit step ends on a new lattice point.
+    pub fn boundary_points(&self) -> Result<i128, OverflowError> {
+        let edge_lengths = self.edge_indices().map(|(i, j)| {
+            let (a, b) = (self.vertices[i], self.vertices[j]);
+            (a.0 as i128 - b.0 as i128).abs() + (a.1 as i128 - b.1 as i128).abs()
+        });
+
+        arithmetic::sum(edge_lengths, "polygon perimeter")
+    }
+
+    // Pick's theorem: A = I + B/2 - 1, so I = A - B/2 + 1
+    pub fn interior_points(&self) -> Result<i128, OverflowError> {
+        if self.orientation()? == Orientation::Degenerate {
+            return Ok(0);
+        }
+        Ok(self.area()? - self.boundary_points()? / 2 + 1)
+    }
+
+    // Interior and boundary lattice points together, i.e. every cell touched by the polygon
+    pub fn enclosed_points(&self) -> Result<i128, OverflowError> {
+        arithmetic::add(
+            self.interior_points()?,
+            self.boundary_points()?,
+            "points enclosed by the polygon",
+        )
+    }
+
+    // Returns the indices of the first pair of edges that touch or cross each other, edge `i`
+    // being the one that starts at vertex `i`. Neighbouring edges are only reported when they
+    // fold back onto each other.
+    pub fn find_self_intersection(&self) -> Option<(usize, usize)> {
+        let n = self.vertices.len();
+        if n < 3 {
+            return if n == 2 { Some((0, 1)) } else { None };
+        }
+
+        for i in 0..n {
+            for j in (i + 1)..n {
+                let are_neighbours = j == i + 1 || (i == 0 && j == n - 1);
+                let (a_start, a_end) = (self.vertices[i], self.vertices[(i + 1) % n]);
+                let (b_start, b_end) = (self.vertices[j], self.vertices[(j + 1) % n]);
+
+                let intersects = if are_neighbours {
+                    folds_back(a_start, a_end, b_start, b_end)
+                } else {
+                    segments_touch(a_start, a_end, b_start, b_end)
+                };
+
+                if intersects {
+                    return Some((i, j));
+                }
+            }
+        }
+
+        None
+    }
+
+    pub fn is_simple(&self) -> bool {
+        self.find_self_intersection().is_none()
+    }
+
+    pub fn is_on_boundary(&self, point: Point) -> bool {
+        self.edge_indices().any(|(i, j)| {
+            let (a, b) = (self.vertices[i], self.vertices[j]);
+            segments_touch(a, b, point, point)
+        })
+    }
+
+    // Strictly inside the polygon, found by casting a ray towards growing second coordinate and
+    // counting the edges it crosses. The half-open range keeps a ray passing through a vertex from
+    // being counted twice.
+    pub fn contains(&self, point: Point) -> bool {
+        if self.is_on_boundary(point) {
+            return false;
+        }
+
+        let crossings = self
+            .edge_indices()
+            .filter(|(i, j)| {
+                let (a, b) = (self.vertices[*i], self.vertices[*j]);
+                a.1 == b.1 && a.1 > point.1 && a.0.min(b.0) <= point.0 && point.0 < a.0.max(b.0)
+            })
+            .count();
+
+        crossings % 2 == 1
+    }
+
+    fn edge_indices(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..self.vertices.len()).map(|i| (i, (i + 1) % self.vertices.len()))
+    }
+}
+
+fn orientation_of(signed_area: i128) -> Orientation {
+    match signed_area {
+        area if area > 0 => Orientation::CounterClockwise,
+        area if area < 0 => Orientation::Clockwise,
+        _ => Orientation::Degenerate,
+    }
+}
+
+fn remove_collinear_vertices(vertices: Vec<Point>) -> Vec<Point> {
+    let n = vertices.len();
+    if n < 3 {
+        return vertices;
+    }
+
+    let mut simplified: Vec<Point> = Vec::new();
+    for i in 0..n {
+        let previous = vertices[(i + n - 1) % n];
+        let current = vertices[i];
+        let next = vertices[(i + 1) % n];
+
+        if direction(previous, current) != direction(current, next) {
+            simplified.push(current);
+        }
+    }
+
+    simplified
+}
+
+fn direction(from: Point, to: Point) -> Point {
+    ((to.0 - from.0).signum(), (to.1 - from.1).signum())
+}
+
+// Axis-aligned segments are their own bounding boxes, so they touch exactly when the boxes do
+fn segments_touch(a_start: Point, a_end: Point, b_start: Point, b_end: Point) -> bool {
+    let overlaps =
+        |a0: i64, a1: i64, b0: i64, b1: i64| a0.min(a1) <= b0.max(b1) && b0.min(b1) <= a0.max(a1);
+
+    overlaps(a_start.0, a_end.0, b_start.0, b_end.0)
+        && overlaps(a_start.1, a_end.1, b_start.1, b_end.1)
+}
+
+fn folds_back(a_start: Point, a_end: Point, b_start: Point, b_end: Point) -> bool {
+    let (a_dir, b_dir) = (direction(a_start, a_end), direction(b_start, b_end));
+    a_dir.0 == -b_dir.0 && a_dir.1 == -b_dir.1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_square() -> Polygon {
+        Polygon::new(vec![(0, 0), (1, 0), (1, 1), (0, 1)]).unwrap()
+    }
+
+    // An L shape, the 2×2 notch cut out of the top right corner of a 4×4 square
+    fn concave_polygon() -> Polygon {
+        Polygon::new(vec![(0, 0), (4, 0), (4, 2), (2, 2), (2, 4), (0, 4)]).unwrap()
+    }
+
+    // The third edge goes back down through the first one
+    fn self_intersecting_polygon() -> Polygon {
+        Polygon::new(vec![(0, 0), (2, 0), (2, 2), (1, 2), (1, -1), (0, -1)]).unwrap()
+    }
+
+    #[test]
+    fn unit_square_has_no_interior_points() {
+        let square = unit_square();
+
+        assert_eq!(square.signed_area(), Ok(1));
+        assert_eq!(square.area(), Ok(1));
+        assert_eq!(square.orientation(), Ok(Orientation::CounterClockwise));
+        assert_eq!(square.boundary_points(), Ok(4));
+        assert_eq!(square.interior_points(), Ok(0));
+        assert_eq!(square.enclosed_points(), Ok(4));
+        assert!(square.is_simple());
+    }
+
+    #[test]
+    fn concave_polygon_counts_points_with_picks_theorem() {
+        let polygon = concave_polygon();
+
+        assert_eq!(polygon.area(), Ok(12));
+        assert_eq!(polygon.orientation(), Ok(Orientation::CounterClockwise));
+        assert_eq!(polygon.boundary_points(), Ok(16));
+        assert_eq!(polygon.interior_points(), Ok(5));
+        assert_eq!(polygon.find_self_intersection(), None);
+    }
+
+    #[test]
+    fn clockwise_polygon_has_negative_signed_area() {
+        let polygon = Polygon::new(vec![(0, 0), (0, 2), (2, 2), (2, 0)]).unwrap();
+
+        assert_eq!(polygon.signed_area(), Ok(-4));
+        assert_eq!(polygon.area(), Ok(4));
+        assert_eq!(polygon.orientation(), Ok(Orientation::Clockwise));
+        assert_eq!(polygon.boundary_points(), Ok(8));
+        assert_eq!(polygon.interior_points(), Ok(1));
+    }
+
+    #[test]
+    fn self_intersecting_polygon_reports_the_crossing_edges() {
+        let polygon = self_intersecting_polygon();
+
+        assert_eq!(polygon.find_self_intersection(), Some((0, 3)));
+        assert!(!polygon.is_simple());
+    }
+
+    #[test]
+    fn points_on_an_edge_are_not_contained() {
+        let polygon = concave_polygon();
+
+        assert!(polygon.contains((1, 1)));
+        assert!(polygon.contains((3, 1)));
+        assert!(!polygon.is_on_boundary((1, 1)));
+
+        for point in [(4, 1), (2, 2), (0, 0), (1, 4), (2, 3)] {
+            assert!(polygon.is_on_boundary(point), "{:?}", point);
+            assert!(!polygon.contains(point), "{:?}", point);
+        }
+
+        assert!(!polygon.contains((3, 3)));
+        assert!(!polygon.is_on_boundary((3, 3)));
+        assert!(!polygon.contains((5, 1)));
+    }
+
+    #[test]
+    fn diagonal_edges_are_rejected() {
+        assert!(Polygon::new(vec![(0, 0), (1, 1), (0, 1)]).is_err());
+    }
+}
@@ -1,7 +1,14 @@
-use std::fs;
+use crate::config::Config;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
 
 const DAY_ARG_PREFIX: &str = "--day=";
 const PART_ARG_PREFIX: &str = "--part=";
+pub const BYTE_ORDER_MARK: char = '\u{feff}';
+
+// Inputs used to live directly in `input_data/day_N` before there was more than one year
+pub const DEFAULT_YEAR: usize = 2023;
 
 pub struct Puzzle {
     pub identifier: PuzzleIdentifier,
@@ -9,65 +16,213 @@ pub struct Puzzle {
 }
 
 pub struct PuzzleIdentifier {
+    pub year: usize,
     pub day: usize,
     pub part: usize,
 }
 
-pub fn read_puzzle(program_args: &Vec<String>) -> Result<Puzzle, String> {
-    read_puzzle_identifier(program_args).and_then(|identifier| {
-        read_puzzle_input_file(identifier.day, identifier.part).map(|input_data| Puzzle {
+pub fn read_puzzle(program_args: &[String], config: &Config) -> Result<Puzzle, String> {
+    read_puzzle_identifier(program_args, config.year).and_then(|identifier| {
+        read_puzzle_input_file(&config.input_dir, identifier.year, identifier.day, identifier.part).map(|input_data| Puzzle {
             identifier,
             input_data,
         })
     })
 }
 
-fn read_puzzle_identifier(program_args: &Vec<String>) -> Result<PuzzleIdentifier, String> {
+pub fn read_puzzle_identifier(program_args: &[String], year: usize) -> Result<PuzzleIdentifier, String> {
     match (
-        read_arg(&program_args, DAY_ARG_PREFIX),
-        read_arg(&program_args, PART_ARG_PREFIX),
+        read_arg(program_args, DAY_ARG_PREFIX),
+        read_arg(program_args, PART_ARG_PREFIX),
     ) {
-        (Ok(day), Ok(part)) => Ok(PuzzleIdentifier { day, part }),
+        (Ok(day), Ok(part)) => Ok(PuzzleIdentifier { year, day, part }),
         (day_result, part_result) => {
-            let day_error = get_error_string(&day_result);
-            let space = if day_result.is_err() { ", " } else { "" };
-            let part_error = get_error_string(&part_result);
-            Err(format!("{:?}{}{:?}", day_error, space, part_error))
+            let errors = [day_result, part_result]
+                .into_iter()
+                .filter_map(|result| result.err())
+                .collect::<Vec<String>>();
+            Err(errors.join(", "))
         }
     }
 }
 
-fn read_arg(args: &Vec<String>, arg_name: &str) -> Result<usize, String> {
-    let maybe_arg = args
+fn read_arg(args: &[String], arg_name: &str) -> Result<usize, String> {
+    read_number_arg(args, arg_name)?.ok_or(format!("Missing argument: {arg_name}"))
+}
+
+pub fn read_text_arg<'a>(program_args: &'a [String], arg_name: &str) -> Option<&'a str> {
+    program_args
         .iter()
-        .find(|arg| arg.starts_with(arg_name))
-        .ok_or(format!("Missing argument: {arg_name}"));
-
-    maybe_arg.and_then(|arg| {
-        let value_start = arg_name.len();
-        let value_str = &arg[value_start..];
-        value_str.parse::<usize>().map_err(|e| {
-            format!(
-                "Failed to read argument value for '{arg_name}. Error: {:?}",
-                e
-            )
+        .find_map(|arg| arg.strip_prefix(arg_name))
+        .filter(|value| !value.is_empty())
+}
+
+pub fn read_number_arg(program_args: &[String], arg_name: &str) -> Result<Option<usize>, String> {
+    read_text_arg(program_args, arg_name)
+        .map(|value| {
+            value
+                .parse::<usize>()
+                .map_err(|_| format!("Invalid value for {arg_name}{value}, expected a number"))
         })
-    })
+        .transpose()
 }
 
-fn get_error_string<T>(res: &Result<T, String>) -> String {
-    match res {
-        Ok(_) => "".to_string(),
-        Err(e) => e.to_string(),
+pub fn has_flag(program_args: &[String], flag: &str) -> bool {
+    program_args.iter().any(|arg| arg == flag)
+}
+
+// Looks for `<input_dir>/<year>/day_N/<file_name>` first, falling back to the old
+// `<input_dir>/day_N` layout for the default year
+fn puzzle_file_path(input_dir: &str, year: usize, day: usize, file_name: &str) -> String {
+    let path = format!("{}/{}/day_{}/{}", input_dir, year, day, file_name);
+    let legacy_path = format!("{}/day_{}/{}", input_dir, day, file_name);
+
+    if year == DEFAULT_YEAR && !Path::new(&path).exists() && Path::new(&legacy_path).exists() {
+        legacy_path
+    } else {
+        path
     }
 }
 
-fn read_puzzle_input_file(day: usize, part: usize) -> Result<String, String> {
-    let path = format!("./input_data/day_{}/part_{}", day, part);
-    fs::read_to_string(path).map_err(|e| {
+pub fn read_puzzle_input_file(input_dir: &str, year: usize, day: usize, part: usize) -> Result<String, String> {
+    let path = puzzle_file_path(input_dir, year, day, &format!("part_{}", part));
+
+    fs::read_to_string(&path).map_err(|e| {
+        format!(
+            "Failed to read the puzzle input for {year} day {day}, part {part} from {path}. Error was: {:?}",
+            e
+        )
+    })
+}
+
+pub fn open_puzzle_input_file(input_dir: &str, year: usize, day: usize, part: usize) -> Result<BufReader<File>, String> {
+    let path = puzzle_file_path(input_dir, year, day, &format!("part_{}", part));
+
+    File::open(&path).map(BufReader::new).map_err(|e| {
         format!(
-            "Failed to read the puzzle input for day {day}, part {part}. Error was: {:?}",
+            "Failed to open the puzzle input for {year} day {day}, part {part} from {path}. Error was: {:?}",
             e
         )
     })
 }
+
+// Known answers are kept next to the inputs as `answer_M`
+pub fn read_puzzle_answer_file(input_dir: &str, year: usize, day: usize, part: usize) -> Option<String> {
+    fs::read_to_string(puzzle_file_path(input_dir, year, day, &format!("answer_{}", part)))
+        .ok()
+        .map(|answer| answer.trim().to_string())
+}
+
+// Writes `part_M` or `answer_M` where the readers above will look for it and returns the path
+pub fn save_puzzle_file(input_dir: &str, year: usize, day: usize, file_name: &str, contents: &str) -> Result<String, String> {
+    let path = puzzle_file_path(input_dir, year, day, file_name);
+
+    Path::new(&path)
+        .parent()
+        .map(fs::create_dir_all)
+        .unwrap_or(Ok(()))
+        .and_then(|_| fs::write(&path, contents))
+        .map(|_| path.clone())
+        .map_err(|e| format!("Failed to save {path}. Error was: {:?}", e))
+}
+
+// Splits the input into lines the same way no matter where the file was saved: a leading BOM is
+// dropped, `\r\n` endings and trailing whitespace are trimmed and trailing blank lines are skipped.
+pub fn lines(input: &str) -> impl Iterator<Item = &str> {
+    let content = input.strip_prefix(BYTE_ORDER_MARK).unwrap_or(input).trim_end();
+
+    Some(content)
+        .filter(|c| !c.is_empty())
+        .into_iter()
+        .flat_map(|c| c.split('\n'))
+        .map(|line| line.trim_end())
+}
+
+// Streaming counterpart of `lines` for inputs too large to load at once. Blank lines are only
+// counted until a non-blank one follows them, so trailing ones are skipped without buffering.
+pub fn stream_lines<R: BufRead>(reader: R) -> StreamLines<R> {
+    StreamLines {
+        lines: reader.lines(),
+        is_first_line: true,
+        blank_lines: 0,
+        held_back_line: None,
+    }
+}
+
+pub struct StreamLines<R> {
+    lines: io::Lines<R>,
+    is_first_line: bool,
+    blank_lines: usize,
+    held_back_line: Option<String>,
+}
+
+impl<R: BufRead> Iterator for StreamLines<R> {
+    type Item = Result<String, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.held_back_line.is_some() {
+            if self.blank_lines > 0 {
+                self.blank_lines -= 1;
+                return Some(Ok(String::new()));
+            }
+            return self.held_back_line.take().map(Ok);
+        }
+
+        for line in self.lines.by_ref() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => return Some(Err(format!("Failed to read the input. Error was: {:?}", e))),
+            };
+            let line = match self.is_first_line {
+                true => line.strip_prefix(BYTE_ORDER_MARK).unwrap_or(&line).trim_end(),
+                false => line.trim_end(),
+            }
+            .to_string();
+            self.is_first_line = false;
+
+            if line.is_empty() {
+                self.blank_lines += 1;
+            } else if self.blank_lines > 0 {
+                self.held_back_line = Some(line);
+                return self.next();
+            } else {
+                return Some(Ok(line));
+            }
+        }
+
+        None
+    }
+}
+
+// Groups the normalized lines into blocks separated by one or more blank lines
+pub fn blocks(input: &str) -> Blocks<'_> {
+    Blocks {
+        lines: Box::new(lines(input)),
+    }
+}
+
+pub struct Blocks<'a> {
+    lines: Box<dyn Iterator<Item = &'a str> + 'a>,
+}
+
+impl<'a> Iterator for Blocks<'a> {
+    type Item = Vec<&'a str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut block: Vec<&'a str> = Vec::new();
+
+        for line in self.lines.by_ref() {
+            if !line.is_empty() {
+                block.push(line);
+            } else if !block.is_empty() {
+                break;
+            }
+        }
+
+        if block.is_empty() {
+            None
+        } else {
+            Some(block)
+        }
+    }
+}
@@ -0,0 +1,56 @@
+// Small xorshift64* generator. Good enough for generating puzzle inputs and, unlike the system
+// randomness, reproducible from a seed.
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        // A zero state would only ever produce zeroes
+        Rng {
+            state: seed ^ 0x9E37_79B9_7F4A_7C15,
+        }
+        .warmed_up()
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    // Uniform value in `low..high`
+    pub fn range(&mut self, low: usize, high: usize) -> usize {
+        if high <= low {
+            low
+        } else {
+            low + (self.next_u64() % (high - low) as u64) as usize
+        }
+    }
+
+    pub fn chance(&mut self, percent: usize) -> bool {
+        self.range(0, 100) < percent
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.range(0, items.len())]
+    }
+
+    // Fisher-Yates
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.range(0, i + 1));
+        }
+    }
+
+    fn warmed_up(mut self) -> Rng {
+        if self.state == 0 {
+            self.state = 1;
+        }
+        for _ in 0..4 {
+            self.next_u64();
+        }
+        self
+    }
+}
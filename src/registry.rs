@@ -0,0 +1,236 @@
+use crate::config::Config;
+use crate::explain::Explanation;
+use crate::puzzle_reader::PuzzleIdentifier;
+use std::io::BufRead;
+use crate::{
+    aplenty, boat_races, camel_cards, clumsy_crucible, cosmic_expansion, cube_conundrum,
+    floor_will_be_lava, garden, gear_ratios, haunted_wasteland, hot_springs, lavaduct_lagoon,
+    lens_library, mirage_maintenance, pipe_maze, point_of_incidence, scratchcards, trebuchet,
+};
+
+pub type Solver = fn(&str, &Config) -> Result<String, String>;
+pub type StreamSolver = fn(&mut dyn BufRead, &Config) -> Result<String, String>;
+pub type Explainer = fn(&str, &Config) -> Result<Explanation, String>;
+
+pub struct Day {
+    pub day: usize,
+    pub title: &'static str,
+    pub parts: Vec<usize>,
+}
+
+const TITLES_2023: &[(usize, &str)] = &[
+    (1, "Trebuchet?!"),
+    (2, "Cube Conundrum"),
+    (3, "Gear Ratios"),
+    (4, "Scratchcards"),
+    (5, "If You Give A Seed A Fertilizer"),
+    (6, "Wait For It"),
+    (7, "Camel Cards"),
+    (8, "Haunted Wasteland"),
+    (9, "Mirage Maintenance"),
+    (10, "Pipe Maze"),
+    (11, "Cosmic Expansion"),
+    (12, "Hot Springs"),
+    (13, "Point of Incidence"),
+    (14, "Parabolic Reflector Dish"),
+    (15, "Lens Library"),
+    (16, "The Floor Will Be Lava"),
+    (17, "Clumsy Crucible"),
+    (18, "Lavaduct Lagoon"),
+    (19, "Aplenty"),
+];
+
+const SOLVERS_2023: &[(usize, usize, Solver)] = &[
+    (1, 1, |input, _| trebuchet::calibrate_using_digits_only(input).map(|n| n.to_string()).map_err(|e| e.to_string())),
+    (1, 2, |input, config| vocabulary(config).and_then(|vocabulary| trebuchet::calibrate_using_spelled_digits(input, &vocabulary).map(|n| n.to_string()).map_err(|e| e.to_string()))),
+    (2, 1, |input, config| bag(config).and_then(|bag| cube_conundrum::find_possible_games(input, &bag).map(|n| n.to_string()).map_err(|e| e.to_string()))),
+    (2, 2, |input, _| cube_conundrum::power_of_minimal_possible_games(input).map(|n| n.to_string()).map_err(|e| e.to_string())),
+    (3, 1, |input, _| Ok(gear_ratios::count_engine_parts(input).to_string())),
+    (3, 2, |input, config| gear_rules(config).and_then(|rules| gear_ratios::count_gear_ratio(input, &rules).map(|n| n.to_string()).map_err(|e| e.to_string()))),
+    (4, 1, |input, _| scratchcards::sum_scratchcard_points(input).map(|n| n.to_string()).map_err(|e| e.to_string())),
+    (4, 2, |input, _| scratchcards::process_scratchcards(input).map(|n| n.to_string()).map_err(|e| e.to_string())),
+    (5, 1, |input, _| Ok(garden::read_almanac_seed_by_seed(input).to_string())),
+    (5, 2, |input, _| Ok(garden::read_almanac_by_seed_ranges(input).to_string())),
+    (6, 1, |input, _| boat_races::calculate_race_winning_margin(input).map(|n| n.to_string()).map_err(|e| e.to_string())),
+    (6, 2, |input, _| Ok(boat_races::calculate_winning_possibilities(input).to_string())),
+    (7, 1, |input, _| camel_cards::calculate_total_winning(input).map(|n| n.to_string()).map_err(|e| e.to_string())),
+    (7, 2, |input, _| camel_cards::calculate_total_winning_with_jokers(input).map(|n| n.to_string()).map_err(|e| e.to_string())),
+    (8, 1, |input, _| Ok(haunted_wasteland::find_way(input).to_string())),
+    (8, 2, |input, _| haunted_wasteland::find_way_ghosts(input).map(|n| n.to_string()).map_err(|e| e.to_string())),
+    (9, 1, |input, _| mirage_maintenance::oasis_report(input).map(|n| n.to_string()).map_err(|e| e.to_string())),
+    (9, 2, |input, _| mirage_maintenance::oasis_report_backwards(input).map(|n| n.to_string()).map_err(|e| e.to_string())),
+    (10, 1, |input, _| pipe_maze::steps_to_farthest_loop_end(input).map(|n| n.to_string()).map_err(|e| e.to_string())),
+    (10, 2, |input, _| pipe_maze::surface_inside_loop(input).map(|n| n.to_string()).map_err(|e| e.to_string())),
+    (11, 1, |input, _| cosmic_expansion::calculate_distances_between_galaxies(input).map(|n| n.to_string()).map_err(|e| e.to_string())),
+    (12, 1, |input, _| hot_springs::calculate_arrangements(input, false).map(|n| n.to_string()).map_err(|e| e.to_string())),
+    (12, 2, |input, _| hot_springs::calculate_arrangements(input, true).map(|n| n.to_string()).map_err(|e| e.to_string())),
+    (13, 1, |input, _| Ok(point_of_incidence::count_reflections(input).to_string())),
+    (15, 1, |input, _| lens_library::calculate_hash_for_sequence(input).map(|n| n.to_string()).map_err(|e| e.to_string())),
+    (15, 2, |input, _| lens_library::put_lenses_in_boxes(input).map(|n| n.to_string()).map_err(|e| e.to_string())),
+    (16, 1, |input, _| Ok(floor_will_be_lava::calculate_energized_tiles(input).to_string())),
+    (16, 2, |input, _| Ok(floor_will_be_lava::find_best_beam_entry(input).to_string())),
+    (17, 1, |input, _| clumsy_crucible::find_shortest_path(input).map(|n| n.to_string()).ok_or("The crucible can not reach the factory".to_string())),
+    (17, 2, |input, _| clumsy_crucible::find_shortest_path2(input).map(|n| n.to_string()).ok_or("The crucible can not reach the factory".to_string())),
+    (18, 1, |input, _| lavaduct_lagoon::calculate_lagoon_surface(input).map(|n| n.to_string()).map_err(|e| e.to_string())),
+    (18, 2, |input, _| lavaduct_lagoon::calculate_lagoon_surface_part_two(input).map(|n| n.to_string()).map_err(|e| e.to_string())),
+    (19, 1, |input, _| aplenty::sort_parts(input).map(|n| n.to_string()).map_err(|e| e.to_string())),
+];
+
+// Line by line solvers for inputs too large to load at once
+const STREAM_SOLVERS_2023: &[(usize, usize, StreamSolver)] = &[
+    (1, 1, |input, _| trebuchet::calibrate_from_reader(input, &trebuchet::Vocabulary::digits_only()).map(|n| n.to_string())),
+    (1, 2, |input, config| vocabulary(config).and_then(|vocabulary| trebuchet::calibrate_from_reader(input, &vocabulary)).map(|n| n.to_string())),
+    (2, 1, |input, config| bag(config).and_then(|bag| cube_conundrum::find_possible_games_from_reader(input, &bag)).map(|n| n.to_string())),
+    (2, 2, |input, _| cube_conundrum::power_of_minimal_possible_games_from_reader(input).map(|n| n.to_string())),
+    (4, 1, |input, _| scratchcards::sum_scratchcard_points_from_reader(input).map(|n| n.to_string())),
+    (4, 2, |input, _| scratchcards::process_scratchcards_from_reader(input).map(|n| n.to_string())),
+    (9, 1, |input, _| mirage_maintenance::oasis_report_from_reader(input, false).map(|n| n.to_string())),
+    (9, 2, |input, _| mirage_maintenance::oasis_report_from_reader(input, true).map(|n| n.to_string())),
+    (12, 1, |input, _| hot_springs::calculate_arrangements_from_reader(input, false).map(|n| n.to_string())),
+    (12, 2, |input, _| hot_springs::calculate_arrangements_from_reader(input, true).map(|n| n.to_string())),
+    (15, 1, |input, _| lens_library::calculate_hash_for_sequence_from_reader(input).map(|n| n.to_string())),
+    (15, 2, |input, _| lens_library::put_lenses_in_boxes_from_reader(input).map(|n| n.to_string())),
+];
+
+const EXPLAINERS_2023: &[(usize, usize, Explainer)] = &[
+    (1, 1, |input, _| Ok(trebuchet::explain_calibration(input, &trebuchet::Vocabulary::digits_only()))),
+    (1, 2, |input, config| vocabulary(config).map(|vocabulary| trebuchet::explain_calibration(input, &vocabulary))),
+    (2, 1, |input, config| bag(config).map(|bag| cube_conundrum::explain_games(input, &bag))),
+    (2, 2, |input, config| bag(config).map(|bag| cube_conundrum::explain_games(input, &bag))),
+    (4, 2, |input, _| scratchcards::explain_card_copies(input)),
+    (5, 1, |input, _| garden::explain_seeds(input)),
+    (7, 1, |input, _| Ok(camel_cards::explain_total_winning(input, false))),
+    (7, 2, |input, _| Ok(camel_cards::explain_total_winning(input, true))),
+    (13, 1, |input, _| Ok(point_of_incidence::explain_reflections(input))),
+];
+
+// Lines or steps of the input that look suspicious, in the same shape as an explanation
+const REPORTS_2023: &[(usize, usize, Explainer)] = &[
+    (1, 1, |input, config| vocabulary(config).map(|vocabulary| trebuchet::calibration_report(input, &vocabulary, false))),
+    (1, 2, |input, config| vocabulary(config).map(|vocabulary| trebuchet::calibration_report(input, &vocabulary, true))),
+    (2, 1, |input, config| bag(config).and_then(|bag| cube_conundrum::bag_report(input, &bag, at_least(config)?))),
+    (2, 2, |input, config| bag(config).and_then(|bag| cube_conundrum::bag_report(input, &bag, at_least(config)?))),
+];
+
+// Every year gets its own table of solvers, keyed by day and part
+fn solvers(year: usize) -> &'static [(usize, usize, Solver)] {
+    match year {
+        2023 => SOLVERS_2023,
+        _ => &[],
+    }
+}
+
+fn stream_solvers(year: usize) -> &'static [(usize, usize, StreamSolver)] {
+    match year {
+        2023 => STREAM_SOLVERS_2023,
+        _ => &[],
+    }
+}
+
+fn explainers(year: usize) -> &'static [(usize, usize, Explainer)] {
+    match year {
+        2023 => EXPLAINERS_2023,
+        _ => &[],
+    }
+}
+
+fn reports(year: usize) -> &'static [(usize, usize, Explainer)] {
+    match year {
+        2023 => REPORTS_2023,
+        _ => &[],
+    }
+}
+
+fn titles(year: usize) -> &'static [(usize, &'static str)] {
+    match year {
+        2023 => TITLES_2023,
+        _ => &[],
+    }
+}
+
+pub fn solve(identifier: &PuzzleIdentifier, input: &str, config: &Config) -> Option<Result<String, String>> {
+    solvers(identifier.year)
+        .iter()
+        .find(|(day, part, _)| *day == identifier.day && *part == identifier.part)
+        .map(|(_, _, solver)| solver(input, config))
+}
+
+pub fn solve_stream(identifier: &PuzzleIdentifier, input: &mut dyn BufRead, config: &Config) -> Option<Result<String, String>> {
+    stream_solvers(identifier.year)
+        .iter()
+        .find(|(day, part, _)| *day == identifier.day && *part == identifier.part)
+        .map(|(_, _, solver)| solver(input, config))
+}
+
+pub fn explain(identifier: &PuzzleIdentifier, input: &str, config: &Config) -> Option<Result<Explanation, String>> {
+    explainers(identifier.year)
+        .iter()
+        .find(|(day, part, _)| *day == identifier.day && *part == identifier.part)
+        .map(|(_, _, explainer)| explainer(input, config))
+}
+
+pub fn report(identifier: &PuzzleIdentifier, input: &str, config: &Config) -> Option<Result<Explanation, String>> {
+    reports(identifier.year)
+        .iter()
+        .find(|(day, part, _)| *day == identifier.day && *part == identifier.part)
+        .map(|(_, _, reporter)| reporter(input, config))
+}
+
+// Days with a published puzzle, including the ones without any part solved yet
+pub fn days(year: usize) -> Vec<Day> {
+    titles(year)
+        .iter()
+        .map(|&(day, title)| Day {
+            day,
+            title,
+            parts: solvers(year)
+                .iter()
+                .filter(|(solved_day, _, _)| *solved_day == day)
+                .map(|(_, part, _)| *part)
+                .collect(),
+        })
+        .collect()
+}
+
+// The spelled digits are set under `[day_1]` with `vocabulary`, `vocabulary_file`, `max_number`
+// and `longest_match`
+fn vocabulary(config: &Config) -> Result<trebuchet::Vocabulary, String> {
+    trebuchet::Vocabulary::build(
+        config.day_text_param(1, "vocabulary").unwrap_or("english"),
+        config.day_number_param(1, "max_number", 9)?,
+        config.day_text_param(1, "vocabulary_file"),
+        config.day_flag_param(1, "longest_match", false)?,
+    )
+}
+
+// The bag contents come from `bag` or `bag_file` under `[day_2]`, with `max_<color>` keys changing
+// a single color and `unknown_max` allowing cubes of colors the bag does not list
+fn bag(config: &Config) -> Result<cube_conundrum::BagConstraints, String> {
+    let overrides = config
+        .day_params(2)
+        .into_iter()
+        .filter_map(|(name, value)| name.strip_prefix("max_").map(|color| (color, value)))
+        .collect::<Vec<(&str, &str)>>();
+
+    cube_conundrum::BagConstraints::build(
+        config.day_text_param(2, "bag_file"),
+        config.day_text_param(2, "bag"),
+        &overrides,
+        config.day_number_param(2, "unknown_max", 0)?,
+    )
+}
+
+pub fn gear_rules(config: &Config) -> Result<gear_ratios::GearRules, String> {
+    gear_ratios::GearRules::build(
+        config.day_text_param(3, "gear_symbols").unwrap_or("*"),
+        config.day_number_param(3, "gear_neighbours", 2)?,
+        config.day_text_param(3, "gear_aggregation").unwrap_or("product"),
+    )
+}
+
+fn at_least(config: &Config) -> Result<Option<usize>, String> {
+    match config.day_text_param(2, "at_least") {
+        Some(_) => config.day_number_param(2, "at_least", 0).map(Some),
+        None => Ok(None),
+    }
+}
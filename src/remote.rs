@@ -0,0 +1,334 @@
+use crate::config::{Config, SESSION_ENV_VAR};
+use crate::puzzle_reader::{self, PuzzleIdentifier};
+use crate::{http, registry};
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DAY_ARG_PREFIX: &str = "--day=";
+const PART_ARG_PREFIX: &str = "--part=";
+const ANSWER_ARG_PREFIX: &str = "--answer=";
+const FORCE_FLAG: &str = "--force";
+const NEXT_SUBMISSION_FILE: &str = ".next_submission";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    Wrong,
+    // Seconds left before the site accepts another answer
+    Wait(u64),
+    AlreadySolved,
+    Unknown(String),
+}
+
+impl Verdict {
+    fn describe(&self) -> String {
+        match self {
+            Verdict::Correct => "That's the right answer".to_string(),
+            Verdict::TooHigh => "Wrong answer, it is too high".to_string(),
+            Verdict::TooLow => "Wrong answer, it is too low".to_string(),
+            Verdict::Wrong => "Wrong answer".to_string(),
+            Verdict::Wait(seconds) => format!("Answered too recently, wait {seconds}s"),
+            Verdict::AlreadySolved => "This part is already solved".to_string(),
+            Verdict::Unknown(text) => format!("Unexpected response: {text}"),
+        }
+    }
+}
+
+// Downloads the input of a day into the input directory, unless it is already there. The site
+// gives one input per day, so it is saved for both parts.
+pub fn fetch(program_args: &[String], config: &Config) -> Result<String, String> {
+    let day = read_day(program_args)?;
+    let force = puzzle_reader::has_flag(program_args, FORCE_FLAG);
+
+    let is_cached = [1, 2].iter().all(|&part| {
+        puzzle_reader::read_puzzle_input_file(&config.input_dir, config.year, day, part).is_ok()
+    });
+    if is_cached && !force {
+        return Ok(format!(
+            "The input for {} day {day} is already cached, use {FORCE_FLAG} to download it again",
+            config.year
+        ));
+    }
+
+    let session = read_session(config)?;
+    let (status, body) = http::send(
+        &config.base_url,
+        "GET",
+        &format!("/{}/day/{day}/input", config.year),
+        &[("Cookie", format!("session={session}"))],
+        "",
+    )?;
+    if status != 200 {
+        return Err(format!(
+            "Failed to fetch the input for {} day {day}, the server answered {status}: {}",
+            config.year,
+            body.lines().next().unwrap_or("").trim()
+        ));
+    }
+
+    let paths = [1, 2]
+        .iter()
+        .map(|part| {
+            puzzle_reader::save_puzzle_file(
+                &config.input_dir,
+                config.year,
+                day,
+                &format!("part_{part}"),
+                &body,
+            )
+        })
+        .collect::<Result<Vec<String>, String>>()?;
+
+    Ok(format!("Saved the input to {}", paths.join(" and ")))
+}
+
+// Sends the answer of a part, solved from the local input unless given with `--answer=`. A correct
+// answer is saved as `answer_M`, so `verify` picks it up.
+pub fn submit(program_args: &[String], config: &Config) -> Result<String, String> {
+    let identifier = PuzzleIdentifier {
+        year: config.year,
+        day: read_day(program_args)?,
+        part: puzzle_reader::read_number_arg(program_args, PART_ARG_PREFIX)?
+            .ok_or("Missing argument: --part=".to_string())?,
+    };
+
+    let answer = match puzzle_reader::read_text_arg(program_args, ANSWER_ARG_PREFIX) {
+        Some(answer) => answer.to_string(),
+        None => {
+            let input = puzzle_reader::read_puzzle_input_file(
+                &config.input_dir,
+                identifier.year,
+                identifier.day,
+                identifier.part,
+            )?;
+            // A failed solver must never reach the site, it would be judged as a wrong answer
+            registry::solve(&identifier, &input, config)
+                .ok_or("There is no solution for this puzzle yet".to_string())?
+                .map_err(|e| format!("Not submitting, the solver failed: {e}"))?
+        }
+    };
+
+    // Waiting is tracked locally as well, so a retry loop does not hammer the site
+    let state_path = format!("{}/{NEXT_SUBMISSION_FILE}", config.input_dir);
+    let next_submission = fs::read_to_string(&state_path)
+        .ok()
+        .and_then(|contents| contents.trim().parse::<u64>().ok())
+        .unwrap_or(0);
+    let now = now_secs();
+    if now < next_submission {
+        return Err(format!(
+            "Not submitting {answer}, wait {}s before sending another answer",
+            next_submission - now
+        ));
+    }
+
+    let session = read_session(config)?;
+    let (status, body) = http::send(
+        &config.base_url,
+        "POST",
+        &format!("/{}/day/{}/answer", identifier.year, identifier.day),
+        &[
+            ("Cookie", format!("session={session}")),
+            (
+                "Content-Type",
+                "application/x-www-form-urlencoded".to_string(),
+            ),
+        ],
+        &format!("level={}&answer={}", identifier.part, url_encode(&answer)),
+    )?;
+    if status != 200 {
+        return Err(format!(
+            "Failed to submit the answer, the server answered {status}: {}",
+            body.lines().next().unwrap_or("").trim()
+        ));
+    }
+
+    let verdict = parse_verdict(&body);
+    let wait = match verdict {
+        Verdict::Correct | Verdict::AlreadySolved => 0,
+        Verdict::Wait(seconds) => seconds,
+        _ => parse_retry_wait(&body).unwrap_or(0),
+    }
+    .max(config.submit_interval as u64);
+    fs::write(&state_path, (now + wait).to_string())
+        .map_err(|e| format!("Failed to save {state_path}. Error was: {:?}", e))?;
+
+    let message = format!(
+        "Day {} part {}, answer {answer}: {}",
+        identifier.day,
+        identifier.part,
+        verdict.describe()
+    );
+    match verdict {
+        Verdict::Correct => puzzle_reader::save_puzzle_file(
+            &config.input_dir,
+            identifier.year,
+            identifier.day,
+            &format!("answer_{}", identifier.part),
+            &answer,
+        )
+        .map(|path| format!("{message}, saved to {path}")),
+        _ => Err(message),
+    }
+}
+
+fn read_day(program_args: &[String]) -> Result<usize, String> {
+    puzzle_reader::read_number_arg(program_args, DAY_ARG_PREFIX)?
+        .ok_or("Missing argument: --day=".to_string())
+}
+
+// The session cookie comes from `AOC_SESSION`, or else from the `session_file`
+fn read_session(config: &Config) -> Result<String, String> {
+    let session = match &config.session {
+        Some(session) => session.clone(),
+        None => fs::read_to_string(&config.session_file).map_err(|_| {
+            format!(
+                "No session token, set {SESSION_ENV_VAR} or save it in {}",
+                config.session_file
+            )
+        })?,
+    };
+
+    let session = session.trim();
+    match session.is_empty() || session.contains(|c: char| c.is_whitespace() || c == ';') {
+        true => Err("The session token is empty or malformed".to_string()),
+        false => Ok(session.to_string()),
+    }
+}
+
+// The site answers with an HTML page, the verdict is in its wording
+pub fn parse_verdict(body: &str) -> Verdict {
+    if body.contains("That's the right answer") {
+        Verdict::Correct
+    } else if body.contains("You gave an answer too recently") {
+        Verdict::Wait(parse_time_left(body).unwrap_or(0))
+    } else if body.contains("That's not the right answer") {
+        if body.contains("too high") {
+            Verdict::TooHigh
+        } else if body.contains("too low") {
+            Verdict::TooLow
+        } else {
+            Verdict::Wrong
+        }
+    } else if body.contains("You don't seem to be solving the right level") {
+        Verdict::AlreadySolved
+    } else {
+        Verdict::Unknown(strip_tags(body).chars().take(200).collect())
+    }
+}
+
+// "You have 1m 23s left to wait."
+fn parse_time_left(body: &str) -> Option<u64> {
+    let start = body.find("You have ")? + "You have ".len();
+    let end = start + body[start..].find(" left to wait")?;
+
+    body[start..end]
+        .split_whitespace()
+        .map(|amount| {
+            let unit = amount.chars().last()?;
+            let number = amount[..amount.len() - unit.len_utf8()]
+                .parse::<u64>()
+                .ok()?;
+            match unit {
+                'h' => Some(number * 3600),
+                'm' => Some(number * 60),
+                's' => Some(number),
+                _ => None,
+            }
+        })
+        .sum()
+}
+
+// "Please wait one minute before trying again." or "wait 5 minutes"
+fn parse_retry_wait(body: &str) -> Option<u64> {
+    let start = body.find("wait ")? + "wait ".len();
+    let mut words = body[start..].split_whitespace();
+    let amount = match words.next()? {
+        "one" => 1,
+        number => number.parse::<u64>().ok()?,
+    };
+
+    match words.next()? {
+        unit if unit.starts_with("minute") => Some(amount * 60),
+        unit if unit.starts_with("second") => Some(amount),
+        _ => None,
+    }
+}
+
+fn strip_tags(body: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+
+    for c in body.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+fn url_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verdicts_are_parsed_from_the_wording() {
+        for (text, verdict) in [
+            (
+                "That's the right answer!  You are one gold star closer.",
+                Verdict::Correct,
+            ),
+            (
+                "That's not the right answer;  your answer is too high.",
+                Verdict::TooHigh,
+            ),
+            (
+                "That's not the right answer; your answer is too low.",
+                Verdict::TooLow,
+            ),
+            (
+                "That's not the right answer.  Please wait one minute.",
+                Verdict::Wrong,
+            ),
+            (
+                "You don't seem to be solving the right level.",
+                Verdict::AlreadySolved,
+            ),
+            (
+                "You gave an answer too recently. You have 2m 5s left to wait.",
+                Verdict::Wait(125),
+            ),
+            (
+                "You gave an answer too recently. You have 30s left to wait.",
+                Verdict::Wait(30),
+            ),
+        ] {
+            assert_eq!(parse_verdict(text), verdict, "{text}");
+        }
+    }
+}
@@ -0,0 +1,278 @@
+use crate::config::Config;
+use crate::gear_ratios::{GearRules, Schematic};
+use crate::puzzle_reader::{self, PuzzleIdentifier};
+use crate::{clumsy_crucible, cosmic_expansion, floor_will_be_lava, pipe_maze, registry};
+use std::env;
+use std::io::IsTerminal;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Grey,
+}
+
+impl Color {
+    fn ansi_code(&self) -> &'static str {
+        match self {
+            Color::Red => "1;31",
+            Color::Green => "1;32",
+            Color::Yellow => "33",
+            Color::Blue => "34",
+            Color::Magenta => "1;35",
+            Color::Grey => "90",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Cell {
+    symbol: char,
+    plain_symbol: char,
+    color: Option<Color>,
+}
+
+// A puzzle grid with overlays. Colors only show up on a terminal, so every overlay also picks the
+// symbol used for the plain ASCII output.
+pub struct Canvas {
+    cells: Vec<Vec<Cell>>,
+}
+
+impl Canvas {
+    pub fn from_input(input: &str) -> Canvas {
+        let cells = puzzle_reader::lines(input)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                line.chars()
+                    .map(|c| Cell {
+                        symbol: c,
+                        plain_symbol: c,
+                        color: None,
+                    })
+                    .collect::<Vec<Cell>>()
+            })
+            .collect::<Vec<Vec<Cell>>>();
+
+        Canvas { cells }
+    }
+
+    pub fn set_color(&mut self, position: (usize, usize), color: Color) {
+        if let Some(cell) = self.get_cell_mut(position) {
+            cell.color = Some(color);
+        }
+    }
+
+    pub fn set_symbol(&mut self, position: (usize, usize), symbol: char, color: Color) {
+        if let Some(cell) = self.get_cell_mut(position) {
+            cell.symbol = symbol;
+            cell.plain_symbol = symbol;
+            cell.color = Some(color);
+        }
+    }
+
+    pub fn set_plain_symbol(&mut self, position: (usize, usize), plain_symbol: char) {
+        if let Some(cell) = self.get_cell_mut(position) {
+            cell.plain_symbol = plain_symbol;
+        }
+    }
+
+    pub fn render(&self, use_colors: bool) -> String {
+        self.cells
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| match (use_colors, cell.color) {
+                        (true, Some(color)) => {
+                            format!("\x1b[{}m{}\x1b[0m", color.ansi_code(), cell.symbol)
+                        }
+                        (true, None) => cell.symbol.to_string(),
+                        (false, _) => cell.plain_symbol.to_string(),
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn get_cell_mut(&mut self, position: (usize, usize)) -> Option<&mut Cell> {
+        self.cells
+            .get_mut(position.0)
+            .and_then(|row| row.get_mut(position.1))
+    }
+
+    fn positions(&self) -> Vec<(usize, usize)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .flat_map(|(row, cells)| (0..cells.len()).map(move |col| (row, col)))
+            .collect()
+    }
+}
+
+// Honours the NO_COLOR convention on top of the TTY check
+pub fn stdout_supports_colors() -> bool {
+    std::io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none()
+}
+
+pub fn render_puzzle(
+    identifier: &PuzzleIdentifier,
+    input: &str,
+    use_colors: bool,
+    config: &Config,
+) -> Result<String, String> {
+    let mut canvas = Canvas::from_input(input);
+
+    match (identifier.year, identifier.day) {
+        (2023, 3) => draw_engine_schematic(&mut canvas, input, &registry::gear_rules(config)?),
+        (2023, 10) => draw_pipe_loop(&mut canvas, input),
+        (2023, 11) => draw_galaxies(&mut canvas, input),
+        (2023, 16) => draw_energized_tiles(&mut canvas, input),
+        (2023, 17) => draw_crucible_route(&mut canvas, input, identifier.part == 2)?,
+        (year, day) => return Err(format!("There is no visualization for {year} day {day}")),
+    }
+
+    Ok(canvas.render(use_colors))
+}
+
+// Part numbers in green and the others in red, or as `x` without colors. Gears under the
+// configured rules show in magenta, or as `G`.
+fn draw_engine_schematic(canvas: &mut Canvas, input: &str, gear_rules: &GearRules) {
+    let schematic = Schematic::parse(input);
+
+    for position in canvas.positions() {
+        canvas.set_color(position, Color::Grey);
+    }
+
+    for number in schematic.numbers() {
+        let is_part_number = schematic.is_part_number(number);
+        for column in number.column..number.column + number.length {
+            match is_part_number {
+                true => canvas.set_color((number.row, column), Color::Green),
+                false => {
+                    canvas.set_color((number.row, column), Color::Red);
+                    canvas.set_plain_symbol((number.row, column), 'x');
+                }
+            }
+        }
+    }
+
+    for symbol in schematic.symbols() {
+        canvas.set_color((symbol.row, symbol.column), Color::Blue);
+    }
+
+    for (gear, _) in schematic.gears(gear_rules) {
+        canvas.set_color((gear.row, gear.column), Color::Magenta);
+        canvas.set_plain_symbol((gear.row, gear.column), 'G');
+    }
+}
+
+fn draw_pipe_loop(canvas: &mut Canvas, input: &str) {
+    let pipe_maze::LoopTiles {
+        loop_tiles,
+        enclosed_tiles,
+    } = pipe_maze::find_loop_tiles(input);
+
+    for position in canvas.positions() {
+        canvas.set_color(position, Color::Grey);
+        canvas.set_plain_symbol(position, '.');
+    }
+
+    let symbols = puzzle_reader::lines(input)
+        .map(|line| line.chars().collect::<Vec<char>>())
+        .collect::<Vec<Vec<char>>>();
+
+    for position in loop_tiles.iter() {
+        let pipe = symbols[position.0][position.1];
+        let box_symbol = match pipe {
+            '|' => '│',
+            '-' => '─',
+            'L' => '└',
+            'J' => '┘',
+            '7' => '┐',
+            'F' => '┌',
+            other => other,
+        };
+        canvas.set_symbol(*position, box_symbol, Color::Yellow);
+        canvas.set_plain_symbol(*position, pipe);
+    }
+
+    for position in enclosed_tiles.iter() {
+        canvas.set_symbol(*position, 'I', Color::Green);
+    }
+}
+
+fn draw_galaxies(canvas: &mut Canvas, input: &str) {
+    let observation = cosmic_expansion::observe_galaxies(input);
+
+    for position in canvas.positions() {
+        let is_expanded_row = observation.empty_rows.contains(&position.0);
+        let is_expanded_column = observation.empty_columns.contains(&position.1);
+
+        match (is_expanded_row, is_expanded_column) {
+            (true, true) => canvas.set_symbol(position, '+', Color::Blue),
+            (true, false) => canvas.set_symbol(position, '-', Color::Blue),
+            (false, true) => canvas.set_symbol(position, '|', Color::Blue),
+            (false, false) => canvas.set_color(position, Color::Grey),
+        }
+    }
+
+    for galaxy in observation.galaxies.iter() {
+        canvas.set_symbol(*galaxy, '#', Color::Magenta);
+    }
+}
+
+fn draw_energized_tiles(canvas: &mut Canvas, input: &str) {
+    let energization_map = floor_will_be_lava::get_energization_map(input);
+
+    for position in canvas.positions() {
+        let is_energized = energization_map
+            .get(position.0)
+            .and_then(|row| row.get(position.1))
+            .map(|tile| *tile > 0)
+            .unwrap_or(false);
+
+        if is_energized {
+            canvas.set_color(position, Color::Yellow);
+            canvas.set_plain_symbol(position, '#');
+        } else {
+            canvas.set_color(position, Color::Grey);
+            canvas.set_plain_symbol(position, '.');
+        }
+    }
+}
+
+fn draw_crucible_route(
+    canvas: &mut Canvas,
+    input: &str,
+    ultra_crucible: bool,
+) -> Result<(), String> {
+    let (_, route) = clumsy_crucible::find_best_route(input, ultra_crucible)
+        .ok_or("The crucible cannot reach the factory".to_string())?;
+
+    for position in canvas.positions() {
+        canvas.set_color(position, Color::Grey);
+    }
+
+    for step in route.windows(2) {
+        let (from, to) = (step[0], step[1]);
+        let arrow = if to.0 > from.0 {
+            'v'
+        } else if to.0 < from.0 {
+            '^'
+        } else if to.1 > from.1 {
+            '>'
+        } else {
+            '<'
+        };
+        canvas.set_symbol(to, arrow, Color::Red);
+    }
+
+    if let Some(start) = route.first() {
+        canvas.set_color(*start, Color::Red);
+    }
+
+    Ok(())
+}
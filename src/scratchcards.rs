@@ -1,13 +1,18 @@
+use crate::arithmetic::{self, OverflowError};
+use crate::explain::{Column, Explanation};
+use crate::puzzle_reader;
+use std::collections::VecDeque;
+use std::io::BufRead;
+
 #[derive(Debug)]
-struct ScratchCard {
+pub struct ScratchCard {
     pub id: usize,
     pub num_of_winning_numbers: usize,
 }
 
 impl ScratchCard {
     pub fn parse_many(cards_stack: &str) -> Vec<ScratchCard> {
-        cards_stack
-            .split("\n")
+        puzzle_reader::lines(cards_stack)
             .filter_map(ScratchCard::parse)
             .collect::<Vec<ScratchCard>>()
     }
@@ -66,69 +71,123 @@ impl ScratchCard {
     }
 }
 
-pub fn sum_scratchcard_points(cards_stack: &str) -> usize {
-    cards_stack
-        .split("\n")
-        .filter_map(|line| {
-            line.split(": ").nth(1).map(|numbers| {
-                let mut numbers_split = numbers.split(" | ");
+pub fn sum_scratchcard_points(cards_stack: &str) -> Result<u128, OverflowError> {
+    let points = puzzle_reader::lines(cards_stack)
+        .filter_map(ScratchCard::parse)
+        .map(|card| points_for_matches(card.num_of_winning_numbers))
+        .collect::<Result<Vec<u128>, OverflowError>>()?;
+    arithmetic::sum(points, "sum of card points")
+}
 
-                let winning_numbers: Vec<usize> = numbers_split
-                    .next()
-                    .iter()
-                    .flat_map(|nums_str| {
-                        let nums: Vec<usize> = nums_str
-                            .split_whitespace()
-                            .filter_map(|n_str| n_str.parse::<usize>().ok())
-                            .collect();
-                        nums
-                    })
-                    .collect();
+pub fn sum_scratchcard_points_from_reader<R: BufRead>(cards_stack: R) -> Result<u128, String> {
+    puzzle_reader::stream_lines(cards_stack).try_fold(0, |sum, line| {
+        let matches = ScratchCard::parse(&line?).map_or(0, |card| card.num_of_winning_numbers);
+        let points = points_for_matches(matches)
+            .and_then(|points| arithmetic::add(sum, points, "sum of card points"));
+        points.map_err(|e| e.to_string())
+    })
+}
 
-                let elfs_numbers: Vec<usize> = numbers_split
-                    .next()
-                    .iter()
-                    .flat_map(|nums_str| {
-                        let nums: Vec<usize> = nums_str
-                            .split_whitespace()
-                            .filter_map(|n_str| n_str.parse::<usize>().ok())
-                            .collect();
-                        nums
-                    })
-                    .collect();
+// The first match is worth a point, every other one doubles the points
+fn points_for_matches(matches: usize) -> Result<u128, OverflowError> {
+    match matches {
+        0 => Ok(0),
+        _ => arithmetic::product(std::iter::repeat_n(2, matches - 1), "card points"),
+    }
+}
 
-                elfs_numbers.into_iter().fold(0, |acc, num| {
-                    let is_winning = winning_numbers.contains(&num);
+// A card in the end: the original and every copy of it that was won
+pub struct CardCopies {
+    pub id: usize,
+    pub matches: usize,
+    pub copies: u128,
+}
 
-                    if is_winning {
-                        if acc == 0 {
-                            1
-                        } else {
-                            acc * 2
-                        }
-                    } else {
-                        acc
-                    }
-                })
-            })
-        })
-        .fold(0, |sum, line_points| sum + line_points)
+// Each card wins one copy of the next `matches` cards for every copy of itself, cards past the
+// last one are not won. The copies won for the current card are kept as a running total, a card
+// adds its copies to it once and they are taken off again where its window ends.
+pub fn count_card_copies(cards: &[ScratchCard]) -> Result<Vec<CardCopies>, OverflowError> {
+    let mut ending_at: Vec<u128> = vec![0; cards.len() + 1];
+    let mut won: u128 = 0;
+    let mut card_copies: Vec<CardCopies> = Vec::with_capacity(cards.len());
+
+    for (index, card) in cards.iter().enumerate() {
+        won = arithmetic::sub(won, ending_at[index], "number of card copies")?;
+        let copies = arithmetic::add(1, won, "number of card copies")?;
+
+        let window_end = (index + 1 + card.num_of_winning_numbers).min(cards.len());
+        if window_end > index + 1 {
+            won = arithmetic::add(won, copies, "number of card copies")?;
+            ending_at[window_end] = arithmetic::add(ending_at[window_end], copies, "number of card copies")?;
+        }
+
+        card_copies.push(CardCopies {
+            id: card.id,
+            matches: card.num_of_winning_numbers,
+            copies,
+        });
+    }
+
+    Ok(card_copies)
 }
 
-fn count_card_tree_size(all_cards: &Vec<ScratchCard>, card: &ScratchCard) -> usize {
-    if card.num_of_winning_numbers > 0 {
-       let start = card.id;
-       let end = card.id + card.num_of_winning_numbers;
-       
-       let cards_to_copy = &all_cards[start..end]; 
-       let children: usize = cards_to_copy.iter().fold(0, |acc, copied_card| acc + count_card_tree_size(all_cards, copied_card));
-       children + 1
-    } else {
-        1
+pub fn process_scratchcards(cards_stack: &str) -> Result<u128, OverflowError> {
+    let cards = ScratchCard::parse_many(cards_stack);
+    let card_copies = count_card_copies(&cards)?;
+    arithmetic::sum(card_copies.iter().map(|card| card.copies), "total number of cards")
+}
+
+pub fn explain_card_copies(cards_stack: &str) -> Result<Explanation, String> {
+    let cards = ScratchCard::parse_many(cards_stack);
+    let mut explanation = Explanation::new(vec![Column::number("card"), Column::number("matches"), Column::number("copies")]);
+
+    for card in count_card_copies(&cards).map_err(|e| e.to_string())? {
+        explanation.add_row(vec![card.id.to_string(), card.matches.to_string(), card.copies.to_string()]);
     }
+
+    Ok(explanation)
 }
 
-pub fn process_scratchcards(cards_stack: &str) -> usize {
-  let cards = ScratchCard::parse_many(cards_stack);
-  cards.iter().map(|card| count_card_tree_size(&cards, card)).fold(0, |acc, elem| acc + elem) 
+// Won copies only ever reach the next few cards, so it is enough to remember how many copies are
+// pending for as many cards ahead as a card has numbers
+pub fn process_scratchcards_from_reader<R: BufRead>(cards_stack: R) -> Result<u128, String> {
+    let mut pending_copies: VecDeque<u128> = VecDeque::new();
+    let mut total: u128 = 0;
+
+    for line in puzzle_reader::stream_lines(cards_stack) {
+        let card = match ScratchCard::parse(&line?) {
+            Some(card) => card,
+            None => continue,
+        };
+
+        let copies = arithmetic::add(1, pending_copies.pop_front().unwrap_or(0), "number of card copies")
+            .map_err(|e| e.to_string())?;
+        total = arithmetic::add(total, copies, "total number of cards").map_err(|e| e.to_string())?;
+
+        if pending_copies.len() < card.num_of_winning_numbers {
+            pending_copies.resize(card.num_of_winning_numbers, 0);
+        }
+        for pending in pending_copies.iter_mut().take(card.num_of_winning_numbers) {
+            *pending = arithmetic::add(*pending, copies, "number of card copies").map_err(|e| e.to_string())?;
+        }
+    }
+
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn points_that_do_not_fit_are_an_overflow() {
+        let numbers = (1..=130).map(|n| n.to_string()).collect::<Vec<String>>().join(" ");
+        let card = format!("Card 1: {numbers} | {numbers}");
+
+        assert_eq!(sum_scratchcard_points("Card 1: 41 48 | 48 41 7\nCard 2: 1 | 2"), Ok(2));
+        assert_eq!(
+            sum_scratchcard_points(&card),
+            Err(OverflowError { operation: "card points" })
+        );
+    }
 }
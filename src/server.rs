@@ -0,0 +1,198 @@
+use crate::config::Config;
+use crate::http::{self, Request, Response};
+use crate::puzzle_reader::{self, PuzzleIdentifier};
+use crate::{cli, json, registry};
+use std::net::{TcpListener, TcpStream};
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+use std::time::Instant;
+
+const PORT_ARG_PREFIX: &str = "--port=";
+const DEFAULT_PORT: usize = 8023;
+
+fn error(status: u16, message: &str) -> Response {
+    Response::json(status, json::object(&[("error", json::string(message))]))
+}
+
+// A small HTTP/1.1 server on localhost, every connection gets its own thread and a single request
+pub fn run(program_args: &[String], config: &Config) -> Result<String, String> {
+    let port =
+        puzzle_reader::read_number_arg(program_args, PORT_ARG_PREFIX)?.unwrap_or(DEFAULT_PORT);
+    let port = u16::try_from(port).map_err(|_| format!("Invalid port {port}"))?;
+
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .map_err(|e| format!("Failed to listen on port {port}. Error was: {:?}", e))?;
+    println!("Listening on http://127.0.0.1:{port}");
+
+    serve(listener, config);
+    Ok("Server stopped".to_string())
+}
+
+fn serve(listener: TcpListener, config: &Config) {
+    thread::scope(|scope| {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    scope.spawn(|| handle_connection(stream, config));
+                }
+                Err(e) => println!("Failed to accept a connection: {:?}", e),
+            }
+        }
+    });
+}
+
+fn handle_connection(mut stream: TcpStream, config: &Config) {
+    let started = Instant::now();
+
+    let (request_line, response) = match http::read_request(&stream) {
+        Ok(request) => (
+            format!("{} {}", request.method, request.path),
+            route(&request, config),
+        ),
+        Err(e) => ("invalid request".to_string(), error(400, &e)),
+    };
+
+    if config.verbosity > 0 {
+        println!(
+            "{request_line} {} in {:?}",
+            response.status,
+            started.elapsed()
+        );
+    }
+
+    let _ = http::write_response(&mut stream, &response);
+}
+
+fn route(request: &Request, config: &Config) -> Response {
+    let path = request.path.split('?').next().unwrap_or("");
+    let segments = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<&str>>();
+
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["days"]) => Response::json(200, cli::days_json(config.year)),
+        ("POST", ["solve", day, part]) => solve(day, part, &request.body, config),
+        (_, ["days"]) | (_, ["solve", _, _]) => error(405, "Method not allowed"),
+        _ => error(404, "Not found"),
+    }
+}
+
+fn solve(day: &str, part: &str, body: &[u8], config: &Config) -> Response {
+    let identifier = match (day.parse::<usize>(), part.parse::<usize>()) {
+        (Ok(day), Ok(part)) => PuzzleIdentifier {
+            year: config.year,
+            day,
+            part,
+        },
+        _ => return error(400, "The day and the part have to be numbers"),
+    };
+    let input = match std::str::from_utf8(body) {
+        Ok(input) => input,
+        Err(_) => return error(400, "The input has to be UTF-8 text"),
+    };
+
+    let started = Instant::now();
+    // A bad input should fail its own request instead of the whole server
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        registry::solve(&identifier, input, config)
+    }));
+    let elapsed = started.elapsed();
+
+    match result {
+        Ok(Some(Ok(answer))) => Response::json(
+            200,
+            json::object(&[
+                ("year", identifier.year.to_string()),
+                ("day", identifier.day.to_string()),
+                ("part", identifier.part.to_string()),
+                ("answer", json::string(&answer)),
+                ("time_us", elapsed.as_micros().to_string()),
+            ]),
+        ),
+        // The input or the configuration the solver got is at fault, not the server
+        Ok(Some(Err(e))) => error(422, &format!("The solver failed: {e}")),
+        Ok(None) => error(404, "There is no solution for this puzzle yet"),
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or("unknown panic".to_string());
+            error(500, &format!("The solver failed: {message}"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Environment;
+
+    fn load_config(config_args: &[&str]) -> Config {
+        Config::load_with(
+            Environment::default(),
+            &config_args
+                .iter()
+                .map(|arg| arg.to_string())
+                .collect::<Vec<String>>(),
+        )
+        .unwrap()
+    }
+
+    fn post_solve(day: usize, part: usize, input: &str, config_args: &[&str]) -> Response {
+        let config = load_config(config_args);
+        let request = Request {
+            method: "POST".to_string(),
+            path: format!("/solve/{day}/{part}"),
+            headers: Vec::new(),
+            body: input.as_bytes().to_vec(),
+        };
+        route(&request, &config)
+    }
+
+    #[test]
+    fn solve_returns_the_answer() {
+        let response = post_solve(1, 1, "1abc2\npqr3stu8vwx", &[]);
+        assert_eq!(response.status, 200);
+        assert!(
+            response.body.contains(r#""answer":"50""#),
+            "{}",
+            response.body
+        );
+    }
+
+    #[test]
+    fn solve_is_served_over_a_socket() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let config = load_config(&["--verbosity=0"]);
+        // Never joined, the thread goes away with the test process
+        thread::spawn(move || serve(listener, &config));
+
+        let (status, body) = http::send(
+            &format!("http://127.0.0.1:{port}"),
+            "POST",
+            "/solve/1/1",
+            &[],
+            "1abc2\npqr3stu8vwx",
+        )
+        .unwrap();
+        assert_eq!(status, 200);
+        assert!(body.contains(r#""answer":"50""#), "{body}");
+        assert!(body.contains(r#""day":1,"part":1"#), "{body}");
+    }
+
+    #[test]
+    fn solver_failures_are_errors() {
+        let response = post_solve(2, 1, "Game 1: 3 blue", &["--set=day_2.bag=red"]);
+        assert_eq!(response.status, 422);
+        assert!(response.body.contains(r#""error":"#), "{}", response.body);
+        assert!(!response.body.contains(r#""answer""#), "{}", response.body);
+    }
+
+    #[test]
+    fn unsolved_puzzles_are_not_found() {
+        assert_eq!(post_solve(14, 1, "O....#....", &[]).status, 404);
+    }
+}
@@ -0,0 +1,340 @@
+use crate::config::Config;
+use crate::http::{self, Request, Response};
+use crate::puzzle_reader;
+use std::net::{TcpListener, TcpStream};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const PORT_ARG_PREFIX: &str = "--port=";
+const DEFAULT_PORT: usize = 8024;
+const LOCKOUT: Duration = Duration::from_secs(60);
+
+// A stand-in for the puzzle site, serving the inputs and judging answers from the input directory
+// with the site's wording
+pub fn run(program_args: &[String], config: &Config) -> Result<String, String> {
+    let port =
+        puzzle_reader::read_number_arg(program_args, PORT_ARG_PREFIX)?.unwrap_or(DEFAULT_PORT);
+    let port = u16::try_from(port).map_err(|_| format!("Invalid port {port}"))?;
+
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .map_err(|e| format!("Failed to listen on port {port}. Error was: {:?}", e))?;
+    println!("Stub site listening on http://127.0.0.1:{port}");
+
+    serve(listener, &config.input_dir, config.verbosity > 0);
+    Ok("Stub site stopped".to_string())
+}
+
+fn serve(listener: TcpListener, input_dir: &str, verbose: bool) {
+    // Wrong answers lock everyone out for a while, like on the real site
+    let locked_until: Mutex<Option<Instant>> = Mutex::new(None);
+
+    thread::scope(|scope| {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let locked_until = &locked_until;
+                    scope
+                        .spawn(move || handle_connection(stream, input_dir, locked_until, verbose));
+                }
+                Err(e) => println!("Failed to accept a connection: {:?}", e),
+            }
+        }
+    });
+}
+
+fn handle_connection(
+    mut stream: TcpStream,
+    input_dir: &str,
+    locked_until: &Mutex<Option<Instant>>,
+    verbose: bool,
+) {
+    let (request_line, response) = match http::read_request(&stream) {
+        Ok(request) => (
+            format!("{} {}", request.method, request.path),
+            route(&request, input_dir, locked_until),
+        ),
+        Err(e) => ("invalid request".to_string(), Response::text(400, e)),
+    };
+
+    if verbose {
+        println!("{request_line} {}", response.status);
+    }
+
+    let _ = http::write_response(&mut stream, &response);
+}
+
+fn route(request: &Request, input_dir: &str, locked_until: &Mutex<Option<Instant>>) -> Response {
+    let segments = request
+        .path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<&str>>();
+
+    let has_session = request
+        .header("cookie")
+        .map(|cookie| cookie.contains("session="))
+        .unwrap_or(false);
+    if !has_session {
+        return Response::text(
+            400,
+            "Puzzle inputs differ by user.  Please log in to get your puzzle input.".to_string(),
+        );
+    }
+
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", [year, "day", day, "input"]) => match (year.parse(), day.parse()) {
+            (Ok(year), Ok(day)) => {
+                match puzzle_reader::read_puzzle_input_file(input_dir, year, day, 1) {
+                    Ok(input) => Response::text(200, input),
+                    Err(_) => Response::text(404, "404 Not Found".to_string()),
+                }
+            }
+            _ => Response::text(404, "404 Not Found".to_string()),
+        },
+        ("POST", [year, "day", day, "answer"]) => match (year.parse(), day.parse()) {
+            (Ok(year), Ok(day)) => judge(request, input_dir, year, day, locked_until),
+            _ => Response::text(404, "404 Not Found".to_string()),
+        },
+        _ => Response::text(404, "404 Not Found".to_string()),
+    }
+}
+
+fn judge(
+    request: &Request,
+    input_dir: &str,
+    year: usize,
+    day: usize,
+    locked_until: &Mutex<Option<Instant>>,
+) -> Response {
+    let form = String::from_utf8_lossy(&request.body).to_string();
+    let field = |name: &str| {
+        form.split('&')
+            .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+            .map(|value| value.to_string())
+    };
+    let (level, answer) = match (field("level").and_then(|l| l.parse().ok()), field("answer")) {
+        (Some(level), Some(answer)) => (level, answer),
+        _ => return Response::text(400, "Missing level or answer".to_string()),
+    };
+
+    let mut locked_until = locked_until.lock().unwrap_or_else(|e| e.into_inner());
+    let now = Instant::now();
+    if let Some(left) = locked_until.map(|until| until.saturating_duration_since(now)) {
+        if !left.is_zero() {
+            let left = left.as_secs() + 1;
+            return article(&format!(
+                "You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have {}m {}s left to wait.",
+                left / 60,
+                left % 60
+            ));
+        }
+    }
+
+    let expected = match puzzle_reader::read_puzzle_answer_file(input_dir, year, day, level) {
+        Some(expected) => expected,
+        None => {
+            return article(
+                "You don't seem to be solving the right level.  Did you already complete it?",
+            )
+        }
+    };
+    if answer == expected {
+        return article(
+            "That's the right answer!  You are one gold star closer to saving the world.",
+        );
+    }
+
+    *locked_until = Some(now + LOCKOUT);
+    let hint = match (answer.parse::<i128>(), expected.parse::<i128>()) {
+        (Ok(answer), Ok(expected)) if answer > expected => "  your answer is too high.",
+        (Ok(answer), Ok(expected)) if answer < expected => "  your answer is too low.",
+        _ => "",
+    };
+    article(&format!(
+        "That's not the right answer;{hint}  Please wait one minute before trying again."
+    ))
+}
+
+fn article(text: &str) -> Response {
+    Response {
+        status: 200,
+        content_type: "text/html",
+        body: format!("<html><body><main><article><p>{text}</p></article></main></body></html>"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Environment;
+    use crate::remote;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process;
+
+    const YEAR: usize = 2023;
+    const INPUT: &str = "1abc2\npqr3stu8vwx\na1b2c3d4e5f\ntreb7uchet";
+    const ANSWER: &str = "142";
+
+    // A stub serving day 1 from its own directory, with part 2 expecting the same answer as part 1,
+    // and a configuration pointing `fetch` and `submit` at it
+    struct Fixture {
+        scratch_dir: PathBuf,
+        client_dir: String,
+        config: Config,
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.scratch_dir);
+        }
+    }
+
+    fn start_stub(name: &str) -> Fixture {
+        let scratch_dir =
+            std::env::temp_dir().join(format!("aoc2023-stub-{name}-{}", process::id()));
+        let scratch = scratch_dir.display().to_string();
+        let (site_dir, client_dir) = (format!("{scratch}/site"), format!("{scratch}/client"));
+        let session_file = format!("{scratch}/session");
+
+        fs::create_dir_all(&client_dir).unwrap();
+        fs::write(&session_file, "stub-session\n").unwrap();
+        for (file_name, contents) in [
+            ("part_1", INPUT),
+            ("answer_1", ANSWER),
+            ("answer_2", ANSWER),
+        ] {
+            puzzle_reader::save_puzzle_file(&site_dir, YEAR, 1, file_name, contents).unwrap();
+        }
+
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        // Never joined, the thread goes away with the test process
+        thread::spawn(move || serve(listener, &site_dir, false));
+
+        let config = Config::load_with(
+            Environment::default(),
+            &[
+                format!("--input-dir={client_dir}"),
+                format!("--year={YEAR}"),
+                format!("--set=base_url=http://127.0.0.1:{port}"),
+                format!("--set=session_file={session_file}"),
+                "--set=submit_interval=0".to_string(),
+            ],
+        )
+        .unwrap();
+
+        Fixture {
+            scratch_dir,
+            client_dir,
+            config,
+        }
+    }
+
+    fn args(extra: &[&str]) -> Vec<String> {
+        ["--day=1"]
+            .iter()
+            .chain(extra)
+            .map(|arg| arg.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn fetch_downloads_the_input_once() {
+        let stub = start_stub("fetch");
+
+        let fetched = remote::fetch(&args(&[]), &stub.config).unwrap();
+        assert!(fetched.contains("Saved"), "{fetched}");
+        for part in [1, 2] {
+            let saved =
+                puzzle_reader::read_puzzle_input_file(&stub.client_dir, YEAR, 1, part).unwrap();
+            assert_eq!(saved, INPUT);
+        }
+
+        let cached = remote::fetch(&args(&[]), &stub.config).unwrap();
+        assert!(cached.contains("already cached"), "{cached}");
+    }
+
+    #[test]
+    fn submit_sends_the_solved_answer_and_saves_it() {
+        let stub = start_stub("submit");
+        remote::fetch(&args(&[]), &stub.config).unwrap();
+
+        let verdict = remote::submit(&args(&["--part=1"]), &stub.config).unwrap();
+        assert!(verdict.contains("right answer"), "{verdict}");
+        assert_eq!(
+            puzzle_reader::read_puzzle_answer_file(&stub.client_dir, YEAR, 1, 1).as_deref(),
+            Some(ANSWER)
+        );
+    }
+
+    #[test]
+    fn submit_waits_after_a_wrong_answer() {
+        let stub = start_stub("wait");
+
+        let too_high =
+            remote::submit(&args(&["--part=2", "--answer=143"]), &stub.config).unwrap_err();
+        assert!(too_high.contains("too high"), "{too_high}");
+
+        let local_wait =
+            remote::submit(&args(&["--part=2", "--answer=141"]), &stub.config).unwrap_err();
+        assert!(local_wait.contains("Not submitting"), "{local_wait}");
+
+        fs::remove_file(format!("{}/.next_submission", stub.client_dir)).unwrap();
+        let site_wait =
+            remote::submit(&args(&["--part=2", "--answer=141"]), &stub.config).unwrap_err();
+        assert!(site_wait.contains("too recently"), "{site_wait}");
+    }
+
+    #[test]
+    fn submit_never_sends_a_failed_solution() {
+        let stub = start_stub("failed-solution");
+        remote::fetch(&args(&[]), &stub.config).unwrap();
+        let config = Config::load_with(
+            Environment::default(),
+            &[
+                format!("--input-dir={}", stub.client_dir),
+                format!("--set=base_url={}", stub.config.base_url),
+                format!("--set=session_file={}", stub.config.session_file),
+                "--set=day_1.vocabulary=klingon".to_string(),
+            ],
+        )
+        .unwrap();
+
+        let refused = remote::submit(&args(&["--part=2"]), &config).unwrap_err();
+        assert!(refused.contains("Not submitting"), "{refused}");
+        assert!(!fs::exists(format!("{}/.next_submission", stub.client_dir)).unwrap());
+    }
+
+    #[test]
+    fn the_session_from_the_environment_comes_before_the_file() {
+        let stub = start_stub("environment-session");
+        let environment = Environment {
+            config_file: None,
+            session: Some("stub-session".to_string()),
+        };
+        let config = Config::load_with(
+            environment,
+            &[
+                format!("--input-dir={}", stub.client_dir),
+                format!("--set=base_url={}", stub.config.base_url),
+                format!("--set=session_file={}/missing", stub.client_dir),
+            ],
+        )
+        .unwrap();
+
+        let fetched = remote::fetch(&args(&[]), &config).unwrap();
+        assert!(fetched.contains("Saved"), "{fetched}");
+    }
+
+    #[test]
+    fn requests_without_a_session_are_refused() {
+        let stub = start_stub("no-session");
+
+        let (status, body) =
+            http::send(&stub.config.base_url, "GET", "/2023/day/1/input", &[], "").unwrap();
+        assert_eq!(status, 400);
+        assert!(body.contains("log in"), "{body}");
+    }
+}
@@ -1,75 +1,532 @@
-use regex::{Match, Regex, RegexSet};
-
-pub fn calibrate_using_digits_only(calibration_file: &String) -> usize {
-    calibration_file
-        .split("\n")
-        .map(|line| {
-            let nums: Vec<usize> = line
-                .chars()
-                .map(|c| c.to_digit(10).and_then(|digit| digit.try_into().ok()))
-                .filter_map(|x| x)
-                .collect();
-
-            match (nums.first(), nums.last()) {
-                (Some(first), Some(last)) => first * 10 + last,
-                _ => 0,
-            }
+use crate::arithmetic::{self, OverflowError};
+use crate::explain::{Column, Explanation};
+use crate::puzzle_reader;
+use std::collections::VecDeque;
+use std::fs;
+use std::io::BufRead;
+
+const NUMERALS: [&str; 10] = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+
+// Spelled out numbers from one to twenty, the first nine are the digits
+const LANGUAGES: [(&str, [&str; 20]); 5] = [
+    (
+        "english",
+        [
+            "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+            "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen",
+            "eighteen", "nineteen", "twenty",
+        ],
+    ),
+    (
+        "german",
+        [
+            "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun", "zehn",
+            "elf", "zwölf", "dreizehn", "vierzehn", "fünfzehn", "sechzehn", "siebzehn",
+            "achtzehn", "neunzehn", "zwanzig",
+        ],
+    ),
+    (
+        "french",
+        [
+            "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf", "dix",
+            "onze", "douze", "treize", "quatorze", "quinze", "seize", "dix-sept", "dix-huit",
+            "dix-neuf", "vingt",
+        ],
+    ),
+    (
+        "spanish",
+        [
+            "uno", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve", "diez",
+            "once", "doce", "trece", "catorce", "quince", "dieciséis", "diecisiete", "dieciocho",
+            "diecinueve", "veinte",
+        ],
+    ),
+    (
+        "polish",
+        [
+            "jeden", "dwa", "trzy", "cztery", "pięć", "sześć", "siedem", "osiem", "dziewięć",
+            "dziesięć", "jedenaście", "dwanaście", "trzynaście", "czternaście", "piętnaście",
+            "szesnaście", "siedemnaście", "osiemnaście", "dziewiętnaście", "dwadzieścia",
+        ],
+    ),
+];
+const NO_LANGUAGE: &str = "none";
+const MAX_BUILT_IN_NUMBER: usize = 20;
+
+pub fn calibrate_using_digits_only(calibration_file: &str) -> Result<u128, OverflowError> {
+    calibrate_using_spelled_digits(calibration_file, &Vocabulary::digits_only())
+}
+
+pub fn calibrate_using_spelled_digits(calibration_file: &str, vocabulary: &Vocabulary) -> Result<u128, OverflowError> {
+    let values = puzzle_reader::lines(calibration_file).map(|line| calibration_value(&vocabulary.scanner, line) as u128);
+    arithmetic::sum(values, "sum of calibration values")
+}
+
+pub fn calibrate_from_reader<R: BufRead>(reader: R, vocabulary: &Vocabulary) -> Result<u128, String> {
+    puzzle_reader::stream_lines(reader).try_fold(0, |acc, line| {
+        let value = calibration_value(&vocabulary.scanner, &line?) as u128;
+        arithmetic::add(acc, value, "sum of calibration values").map_err(|e| e.to_string())
+    })
+}
+
+// What was read from one line of the calibration document
+pub struct CalibrationRecord {
+    pub line_number: usize,
+    pub tokens: Vec<CalibrationToken>,
+    pub first_digit: Option<usize>,
+    pub last_digit: Option<usize>,
+    pub value: usize,
+}
+
+pub struct CalibrationToken {
+    pub text: String,
+    // Column of the first character, counting from 1
+    pub column: usize,
+    pub value: usize,
+}
+
+pub fn calibration_records<'a>(calibration_file: &'a str, vocabulary: &'a Vocabulary) -> impl Iterator<Item = CalibrationRecord> + 'a {
+    puzzle_reader::lines(calibration_file)
+        .enumerate()
+        .map(|(index, line)| calibration_record(index + 1, line, vocabulary))
+}
+
+fn calibration_record(line_number: usize, line: &str, vocabulary: &Vocabulary) -> CalibrationRecord {
+    let tokens = vocabulary
+        .scanner
+        .matches(line)
+        .into_iter()
+        .map(|(start, length, value)| CalibrationToken {
+            text: line[start..start + length].to_string(),
+            column: line[..start].chars().count() + 1,
+            value,
         })
-        .fold(0, |acc, elem| acc + elem)
+        .collect::<Vec<CalibrationToken>>();
+    let first_digit = tokens.first().map(|token| leading_digit(token.value));
+    let last_digit = tokens.last().map(|token| token.value % 10);
+
+    CalibrationRecord {
+        line_number,
+        value: match (first_digit, last_digit) {
+            (Some(first), Some(last)) => first * 10 + last,
+            _ => 0,
+        },
+        tokens,
+        first_digit,
+        last_digit,
+    }
 }
 
-pub fn calibrate_using_spelled_digits(calibration_file: &String) -> usize {
-    calibration_file
-        .split("\n")
-        .map(|line| {
-            let nums: Vec<usize> = find_candidates(&line)
+pub fn explain_calibration(calibration_file: &str, vocabulary: &Vocabulary) -> Explanation {
+    let mut explanation = Explanation::new(vec![
+        Column::number("line"),
+        Column::text("text"),
+        Column::text("tokens"),
+        Column::number("first"),
+        Column::number("last"),
+        Column::number("value"),
+    ]);
+    let digit_or_blank = |digit: Option<usize>| digit.map(|d| d.to_string()).unwrap_or_default();
+
+    for (record, line) in calibration_records(calibration_file, vocabulary).zip(puzzle_reader::lines(calibration_file)) {
+        explanation.add_row(vec![
+            record.line_number.to_string(),
+            line.to_string(),
+            record
+                .tokens
                 .iter()
-                .map(|candidate| parse_num(candidate))
-                .filter_map(|x| x)
-                .collect();
+                .map(|token| format!("{}@{}", token.text, token.column))
+                .collect::<Vec<String>>()
+                .join(" "),
+            digit_or_blank(record.first_digit),
+            digit_or_blank(record.last_digit),
+            record.value.to_string(),
+        ]);
+    }
+
+    explanation
+}
+
+// Only the lines worth a second look: without any digit, with a single one, or reading differently
+// with and without the spelled digits. The value and the digit counts are for the part solved.
+pub fn calibration_report(calibration_file: &str, vocabulary: &Vocabulary, with_spelled_digits: bool) -> Explanation {
+    let digits_only = Vocabulary::digits_only();
+    let part_vocabulary = if with_spelled_digits { vocabulary } else { &digits_only };
+    let mut report = Explanation::new(vec![
+        Column::number("line"),
+        Column::text("text"),
+        Column::number("value"),
+        Column::number("numeric"),
+        Column::number("spelled"),
+        Column::text("notes"),
+    ]);
+
+    for (record, line) in calibration_records(calibration_file, part_vocabulary).zip(puzzle_reader::lines(calibration_file)) {
+        let numeric = calibration_value(&digits_only.scanner, line);
+        let spelled = calibration_value(&vocabulary.scanner, line);
+
+        let notes = [
+            (record.tokens.is_empty(), "no digits"),
+            (record.tokens.len() == 1, "single digit"),
+            (numeric != spelled, "numeric and spelled differ"),
+        ]
+        .iter()
+        .filter(|(flagged, _)| *flagged)
+        .map(|(_, note)| *note)
+        .collect::<Vec<&str>>();
 
-            match (nums.first(), nums.last()) {
-                (Some(first), Some(last)) => first * 10 + last,
-                _ => 0,
+        if !notes.is_empty() {
+            report.add_row(vec![
+                record.line_number.to_string(),
+                line.to_string(),
+                record.value.to_string(),
+                numeric.to_string(),
+                spelled.to_string(),
+                notes.join(", "),
+            ]);
+        }
+    }
+
+    report
+}
+
+// A word worth more than 9 counts with its own digits, so `twelve` starts with 1 and ends with 2
+fn calibration_value(scanner: &DigitScanner, line: &str) -> usize {
+    match (scanner.first_digit(line), scanner.last_digit(line)) {
+        (Some(first), Some(last)) => leading_digit(first) * 10 + last % 10,
+        _ => 0,
+    }
+}
+
+fn leading_digit(mut value: usize) -> usize {
+    while value >= 10 {
+        value /= 10;
+    }
+    value
+}
+
+// The words read as digits besides the numerals themselves
+pub struct Vocabulary {
+    scanner: DigitScanner,
+}
+
+impl Vocabulary {
+    pub fn digits_only() -> Vocabulary {
+        Vocabulary::new(Vec::new())
+    }
+
+    // `languages` is a comma separated list of the built-in ones, taking their words up to
+    // `max_number`, and `file` has more words as `word = value` lines. Two words starting at the
+    // same place are read as the longer one, and that has to be allowed with `longest_match`
+    // whenever one word is the beginning of another with a different value, like `four` and
+    // `fourteen`.
+    pub fn build(
+        languages: &str,
+        max_number: usize,
+        file: Option<&str>,
+        longest_match: bool,
+    ) -> Result<Vocabulary, String> {
+        if !(9..=MAX_BUILT_IN_NUMBER).contains(&max_number) {
+            return Err(format!(
+                "The largest spelled number has to be between 9 and {MAX_BUILT_IN_NUMBER}, got {max_number}"
+            ));
+        }
+
+        let mut words: Vec<(String, usize)> = Vec::new();
+        for language in languages.split(',').map(|language| language.trim()) {
+            if language == NO_LANGUAGE {
+                continue;
             }
+            let (_, numbers) = LANGUAGES
+                .iter()
+                .find(|(name, _)| *name == language)
+                .ok_or(format!(
+                    "Unknown vocabulary '{language}', expected {} or {NO_LANGUAGE}",
+                    LANGUAGES
+                        .iter()
+                        .map(|(name, _)| *name)
+                        .collect::<Vec<&str>>()
+                        .join(", ")
+                ))?;
+            words.extend(
+                numbers
+                    .iter()
+                    .take(max_number)
+                    .enumerate()
+                    .map(|(index, word)| (word.to_string(), index + 1)),
+            );
+        }
+
+        if let Some(path) = file {
+            let contents = fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read {path}. Error was: {:?}", e))?;
+            words.extend(parse_vocabulary_file(&contents).map_err(|e| format!("{path}: {e}"))?);
+        }
+
+        validate(&mut words, longest_match)?;
+        Ok(Vocabulary::new(words))
+    }
+
+    fn new(words: Vec<(String, usize)>) -> Vocabulary {
+        let words = NUMERALS
+            .iter()
+            .enumerate()
+            .map(|(value, numeral)| (numeral.to_string(), value))
+            .chain(words)
+            .collect::<Vec<(String, usize)>>();
+
+        Vocabulary {
+            scanner: DigitScanner::new(&words),
+        }
+    }
+}
+
+fn parse_vocabulary_file(contents: &str) -> Result<Vec<(String, usize)>, String> {
+    puzzle_reader::lines(contents)
+        .enumerate()
+        .map(|(index, line)| (index, line.split('#').next().unwrap_or("").trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(index, line)| {
+            let (word, value) = line
+                .split_once('=')
+                .ok_or(format!("line {}: expected word = value", index + 1))?;
+            let value = value.trim().parse::<usize>().map_err(|_| {
+                format!("line {}: '{}' is not a number", index + 1, value.trim())
+            })?;
+            Ok((word.trim().to_string(), value))
         })
-        .fold(0, |acc, elem| acc + elem)
+        .collect()
 }
 
-fn find_candidates(str: &str) -> Vec<&str> {
-    let patterns = [
-        "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", r"\d",
-    ];
-    let regex_set = RegexSet::new(patterns).expect("Incorrect regex patterns");
-    let regexes: Vec<Regex> = regex_set
-        .patterns()
+fn validate(words: &mut Vec<(String, usize)>, longest_match: bool) -> Result<(), String> {
+    if let Some((word, _)) = words
         .iter()
-        .map(|p| Regex::new(p).expect(format!("Failed to build regex for pattern {p}").as_str()))
-        .collect();
+        .find(|(word, _)| word.is_empty() || word.chars().any(|c| c.is_numeric() || c.is_whitespace()))
+    {
+        return Err(format!(
+            "'{word}' can not be a digit word, it has to be letters without spaces or numerals"
+        ));
+    }
 
-    let mut matches = regex_set
-        .matches(str)
-        .into_iter()
-        .filter_map(|index| regexes.get(index))
-        .flat_map(|re| re.find_iter(str).collect::<Vec<Match>>())
-        .collect::<Vec<Match>>();
-
-    matches.sort_by(|a, b| a.start().partial_cmp(&b.start()).unwrap());
-
-    matches.iter().map(|m| &str[m.start()..m.end()]).collect()
-}
-
-fn parse_num(str: &str) -> Option<usize> {
-    match str {
-        "one" => Some(1),
-        "two" => Some(2),
-        "three" => Some(3),
-        "four" => Some(4),
-        "five" => Some(5),
-        "six" => Some(6),
-        "seven" => Some(7),
-        "eight" => Some(8),
-        "nine" => Some(9),
-        other => other.parse::<usize>().ok(),
+    words.sort();
+    words.dedup();
+    if let Some(pair) = words.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        return Err(format!(
+            "'{}' can not mean both {} and {}",
+            pair[0].0, pair[0].1, pair[1].1
+        ));
+    }
+
+    if longest_match {
+        return Ok(());
+    }
+    // Sorted, so the words starting with a given word follow right after it
+    for (index, (word, value)) in words.iter().enumerate() {
+        let conflict = words[index + 1..]
+            .iter()
+            .take_while(|(longer, _)| longer.starts_with(word.as_str()))
+            .find(|(_, longer_value)| longer_value != value);
+
+        if let Some((longer, longer_value)) = conflict {
+            return Err(format!(
+                "'{word}' ({value}) is the beginning of '{longer}' ({longer_value}), so it is ambiguous which one is meant. Set day_1.longest_match = true to read the longer word"
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+// Finds every occurrence of the digit words in a line, overlapping ones included, so `twone` is
+// 2 and 1 and `eightwo` is 8 and 2. The words are matched by an Aho-Corasick automaton in a single
+// pass, with a second automaton of the reversed words for scanning from the end of the line.
+struct DigitScanner {
+    forward: Automaton,
+    backward: Automaton,
+    longest_word: usize,
+}
+
+impl DigitScanner {
+    fn new(words: &[(String, usize)]) -> DigitScanner {
+        let reversed = words
+            .iter()
+            .map(|(word, value)| (word.bytes().rev().collect(), *value))
+            .collect::<Vec<(Vec<u8>, usize)>>();
+        let words = words
+            .iter()
+            .map(|(word, value)| (word.bytes().collect(), *value))
+            .collect::<Vec<(Vec<u8>, usize)>>();
+
+        DigitScanner {
+            forward: Automaton::new(&words),
+            backward: Automaton::new(&reversed),
+            longest_word: words.iter().map(|(word, _)| word.len()).max().unwrap_or(0),
+        }
+    }
+
+    // Start, length and value of the words, ordered by where they start, the longest word for
+    // each start
+    fn matches(&self, line: &str) -> Vec<(usize, usize, usize)> {
+        let mut matches: Vec<(usize, usize, usize)> = Vec::new();
+        self.forward.scan(line.bytes(), |end, length, value| {
+            matches.push((end + 1 - length, length, value));
+            true
+        });
+
+        matches.sort_by_key(|(start, length, _)| (*start, usize::MAX - length));
+        matches.dedup_by_key(|(start, _, _)| *start);
+        matches
+    }
+
+    fn first_digit(&self, line: &str) -> Option<usize> {
+        let mut first: Option<(usize, usize, usize)> = None;
+        self.forward.scan(line.bytes(), |end, length, value| {
+            let start = end + 1 - length;
+            let (first_start, first_length, _) = *first.get_or_insert((start, length, value));
+            if (start, usize::MAX - length) < (first_start, usize::MAX - first_length) {
+                first = Some((start, length, value));
+            }
+            // A longer word ending further on could still start at or before the first one found
+            end < start.min(first_start) + self.longest_word
+        });
+
+        first.map(|(_, _, value)| value)
+    }
+
+    fn last_digit(&self, line: &str) -> Option<usize> {
+        // Going backwards a word is found at its first byte, so the first words found are the
+        // last ones, all starting at the same place
+        let mut last: Option<(usize, usize, usize)> = None;
+        self.backward.scan(line.bytes().rev(), |position, length, value| {
+            match last {
+                Some((last_position, _, _)) if last_position != position => return false,
+                Some((_, last_length, _)) if last_length >= length => {}
+                _ => last = Some((position, length, value)),
+            }
+            true
+        });
+
+        last.map(|(_, _, value)| value)
+    }
+}
+
+struct Automaton {
+    transitions: Vec<[usize; 256]>,
+    // Length and value of every word ending in a state, including through the failure links
+    outputs: Vec<Vec<(usize, usize)>>,
+}
+
+impl Automaton {
+    fn new(words: &[(Vec<u8>, usize)]) -> Automaton {
+        const MISSING: usize = usize::MAX;
+        let mut transitions = vec![[MISSING; 256]];
+        let mut outputs: Vec<Vec<(usize, usize)>> = vec![Vec::new()];
+
+        for (word, value) in words.iter() {
+            let mut state = 0;
+            for &byte in word.iter() {
+                if transitions[state][byte as usize] == MISSING {
+                    transitions[state][byte as usize] = transitions.len();
+                    transitions.push([MISSING; 256]);
+                    outputs.push(Vec::new());
+                }
+                state = transitions[state][byte as usize];
+            }
+            outputs[state].push((word.len(), *value));
+        }
+
+        // Breadth first, so the failure target of a state is always complete before the state
+        let mut failure = vec![0; transitions.len()];
+        let mut queue = VecDeque::new();
+        for next in transitions[0].iter_mut() {
+            match *next {
+                MISSING => *next = 0,
+                child => queue.push_back(child),
+            }
+        }
+
+        while let Some(state) = queue.pop_front() {
+            let inherited = outputs[failure[state]].clone();
+            outputs[state].extend(inherited);
+
+            let fallbacks = transitions[failure[state]];
+            for (next, fallback) in transitions[state].iter_mut().zip(fallbacks) {
+                match *next {
+                    MISSING => *next = fallback,
+                    child => {
+                        failure[child] = fallback;
+                        queue.push_back(child);
+                    }
+                }
+            }
+        }
+
+        Automaton {
+            transitions,
+            outputs,
+        }
+    }
+
+    // Calls `on_match` with the position of the last byte, the length and the value of every
+    // match, until it returns false
+    fn scan<I, F>(&self, bytes: I, mut on_match: F)
+    where
+        I: Iterator<Item = u8>,
+        F: FnMut(usize, usize, usize) -> bool,
+    {
+        let mut state = 0;
+        for (position, byte) in bytes.enumerate() {
+            state = self.transitions[state][byte as usize];
+            for &(length, value) in self.outputs[state].iter() {
+                if !on_match(position, length, value) {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn english(max_number: usize, longest_match: bool) -> Vocabulary {
+        Vocabulary::build("english", max_number, None, longest_match).unwrap()
+    }
+
+    #[test]
+    fn overlapping_words_are_both_read() {
+        let scanner = english(9, false).scanner;
+
+        assert_eq!(calibration_value(&scanner, "twone"), 21);
+        assert_eq!(calibration_value(&scanner, "eightwo"), 82);
+        assert_eq!(calibration_value(&scanner, "oneight"), 18);
+        assert_eq!(scanner.matches("twone"), vec![(0, 3, 2), (2, 3, 1)]);
+    }
+
+    #[test]
+    fn automaton_reports_every_match_through_the_failure_links() {
+        let words = [(b"he".to_vec(), 1), (b"she".to_vec(), 2), (b"hers".to_vec(), 3)];
+        let automaton = Automaton::new(&words);
+
+        let mut found = Vec::new();
+        automaton.scan("ushers".bytes(), |end, length, value| {
+            found.push((end, length, value));
+            true
+        });
+
+        assert_eq!(found, vec![(3, 3, 2), (3, 2, 1), (5, 4, 3)]);
+    }
+
+    #[test]
+    fn prefix_words_need_longest_match() {
+        assert!(Vocabulary::build("english", 14, None, false).is_err());
+
+        let scanner = english(14, true).scanner;
+        assert_eq!(scanner.matches("fourteen"), vec![(0, 8, 14)]);
+        assert_eq!(scanner.first_digit("xfourteenx"), Some(14));
+        assert_eq!(scanner.last_digit("fourteenx"), Some(14));
+        assert_eq!(calibration_value(&scanner, "fourteen"), 14);
+        assert_eq!(calibration_value(&scanner, "sixfour"), 64);
     }
 }
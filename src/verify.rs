@@ -0,0 +1,91 @@
+use crate::config::Config;
+use crate::puzzle_reader::{self, PuzzleIdentifier};
+use crate::registry;
+
+const DAY_ARG_PREFIX: &str = "--day=";
+
+// Solves every implemented part that has an input and compares the result with the `answer_N` file
+// saved next to it. Parts without a saved answer are reported but do not fail the run.
+pub fn run(program_args: &[String], config: &Config) -> Result<String, String> {
+    let day_filter = puzzle_reader::read_number_arg(program_args, DAY_ARG_PREFIX)?;
+
+    let days = registry::days(config.year)
+        .into_iter()
+        .filter(|day| day_filter.map(|d| d == day.day).unwrap_or(true))
+        .collect::<Vec<registry::Day>>();
+
+    if days.is_empty() {
+        return Err(format!("No puzzles to verify for {}", config.year));
+    }
+
+    let mut report: Vec<String> = Vec::new();
+    let (mut passed, mut failed, mut unchecked, mut skipped) = (0, 0, 0, 0);
+
+    for day in days.iter() {
+        for &part in day.parts.iter() {
+            let label = format!("Day {:2} part {}", day.day, part);
+            let identifier = PuzzleIdentifier {
+                year: config.year,
+                day: day.day,
+                part,
+            };
+
+            let input = match puzzle_reader::read_puzzle_input_file(
+                &config.input_dir,
+                config.year,
+                day.day,
+                part,
+            ) {
+                Ok(input) => input,
+                Err(_) => {
+                    skipped += 1;
+                    report.push(format!("{label}: skipped, no input"));
+                    continue;
+                }
+            };
+
+            let answer = match registry::solve(&identifier, &input, config) {
+                Some(Ok(answer)) => answer,
+                Some(Err(e)) => {
+                    failed += 1;
+                    report.push(format!("{label}: FAILED, {e}"));
+                    continue;
+                }
+                None => String::new(),
+            };
+
+            match puzzle_reader::read_puzzle_answer_file(
+                &config.input_dir,
+                config.year,
+                day.day,
+                part,
+            ) {
+                Some(expected) if expected == answer => {
+                    passed += 1;
+                    report.push(format!("{label}: OK {answer}"));
+                }
+                Some(expected) => {
+                    failed += 1;
+                    report.push(format!(
+                        "{label}: FAILED, got {answer} but expected {expected}"
+                    ));
+                }
+                None => {
+                    unchecked += 1;
+                    report.push(format!("{label}: {answer}, no expected answer"));
+                }
+            }
+        }
+    }
+
+    report.push(format!(
+        "{passed} passed, {failed} failed, {unchecked} without an expected answer, {skipped} without an input"
+    ));
+
+    let summary = report.join("\n");
+    if failed == 0 {
+        Ok(summary)
+    } else {
+        Err(summary)
+    }
+}